@@ -1,4 +1,21 @@
 #!/usr/bin/env python
 # -*- coding: utf-8 -*-
 
-from .bstr import Bytes, Bstr, c_bytes_p, c_bstr_p, bstr_api  # noqa: F401
+from .bstr import (  # noqa: F401
+    Bytes,
+    Bstr,
+    c_bytes_p,
+    c_bstr_p,
+    bstr_str_callback,
+    bstr_char_callback,
+    bytes_callback,
+    bstr_malloc_callback,
+    bstr_free_callback,
+    bstr_realloc_callback,
+    bstr_lookup_callback,
+    bstr_char_predicate,
+    bytes_predicate,
+    bstr_token_callback,
+    bytes_read_callback,
+    bstr_api,
+)
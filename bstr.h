@@ -1,6 +1,7 @@
 #ifndef __BSTR_H___
 #define __BSTR_H___
 
+#include <stdbool.h>
 #include <stddef.h>
 #include <stdint.h>
 
@@ -104,11 +105,30 @@ typedef struct bstr_t {
 #define NPOS ((size_t)(-1))
 #endif
 
+/// Unicode normalization forms accepted by bstr_normalize()
+#define BSTR_NFC  0
+#define BSTR_NFD  1
+#define BSTR_NFKC 2
+#define BSTR_NFKD 3
+
+/// Content classes returned by bstr_classify()
+#define BSTR_CLASS_EMPTY   0
+#define BSTR_CLASS_ASCII   1
+#define BSTR_CLASS_UNICODE 2
+
+/// Character classes of the tokens passed by bstr_for_each_token()
+#define BSTR_TOKEN_OTHER  0
+#define BSTR_TOKEN_LETTER 1
+#define BSTR_TOKEN_DIGIT  2
+#define BSTR_TOKEN_PUNCT  3
+#define BSTR_TOKEN_SPACE  4
+
 ////////////////////////////////////////////////////////////////////////////////
 
 /// @brief Create an empty byte array in place.
 /// @param [out] buf The buffer of the byte array to be initialized
 /// @note No memory allocation occurs for empty arrays
+/// @note Releasing an empty array is also free, it never touches the heap
 /// @note Thread-safe through reference counting
 extern void bytes_init(bytes_t *buf);
 
@@ -129,6 +149,7 @@ extern size_t bytes_size(const bytes_t *bytes);
 /// @brief Create an empty byte array.
 /// @return The new array which must be released by \ref bytes_release()
 /// @note No memory allocation occurs for empty arrays
+/// @note Releasing an empty array is also free, it never touches the heap
 /// @note Thread-safe through reference counting
 extern bytes_t bytes_new(void);
 
@@ -149,6 +170,13 @@ extern bytes_t bytes_alloc(size_t len);
 /// @note All bytes are initialized to zero value
 extern bytes_t bytes_zalloc(size_t len);
 
+/// @brief Allocate a byte array with every byte set to a value.
+/// @param [in] byte The value of every byte
+/// @param [in] len Number of bytes of the array
+/// @return The new array which must be released by bytes_release()
+/// @note No memory allocation occurs if len is 0
+extern bytes_t bytes_fill(uint8_t byte, size_t len);
+
 /// @brief Create a bytes object from a static byte array without copying.
 /// @param [in] static_data Pointer to static data that must outlive the bytes object
 /// @param [in] len Number of bytes in the array
@@ -182,9 +210,31 @@ extern bytes_t bytes_copy_from_slice(const void *data, size_t len);
 /// @note Zero-copy operation - shares data with source
 /// @note Thread-safe through reference counting
 /// @note The returned slice shares the same underlying data
-/// @note Invalid indices will be clamped to valid range
+/// @note Invalid indices will be clamped to valid range, see bytes_slice_checked()
 extern bytes_t bytes_slice(const bytes_t *bytes, size_t start, size_t stop);
 
+/// @brief Create a view into a portion of a bytes object, validating the range.
+/// @param [in] bytes The source bytes object
+/// @param [in] start The start index of the slice
+/// @param [in] stop The end index of the slice, or NPOS for end
+/// @param [out] ok Set to false if start > stop or stop > length
+/// @return New bytes object representing the slice, or an empty one if the range is invalid
+/// @note Zero-copy operation - shares data with source
+/// @note Unlike bytes_slice(), indices are never clamped
+extern bytes_t bytes_slice_checked(const bytes_t *bytes, size_t start, size_t stop, bool *ok);
+
+/// @brief Create a view of the first bytes of a bytes object.
+/// @param [in] bytes The source bytes object
+/// @param [in] len Number of bytes to keep, clamped to the length
+/// @return New bytes object sharing data with the source
+extern bytes_t bytes_prefix(const bytes_t *bytes, size_t len);
+
+/// @brief Create a view of a bytes object from an offset to the end.
+/// @param [in] bytes The source bytes object
+/// @param [in] from Offset of the first byte to keep, clamped to the length
+/// @return New bytes object sharing data with the source
+extern bytes_t bytes_suffix(const bytes_t *bytes, size_t from);
+
 /// @brief Create a new copy of a bytes object.
 /// @param [in] bytes The source bytes object to clone
 /// @return New bytes object containing a copy of the data
@@ -193,6 +243,16 @@ extern bytes_t bytes_slice(const bytes_t *bytes, size_t start, size_t stop);
 /// @note The source object is unaffected
 extern bytes_t bytes_clone(const bytes_t *bytes);
 
+/// @brief Copy the contents of a bytes object into a plain heap buffer owned by the caller.
+/// @param [in] bytes The source bytes object
+/// @param [out] out_ptr Receives the new buffer, or NULL if allocation fails
+/// @param [out] out_len Receives the length of the buffer in bytes, or 0 if allocation fails
+/// @return true on success, false if allocation fails
+/// @note Always copies, unlike bytes_ptr() the buffer stays valid after bytes is released
+/// @note An empty array still yields a non-NULL buffer of length 0
+/// @note Caller must free the returned buffer using bstr_mem_free()
+extern bool bytes_into_c_buffer(const bytes_t *bytes, void **out_ptr, size_t *out_len);
+
 /// @brief Release a reference to a bytes object.
 /// @param [in,out] bytes The bytes object to release
 /// @note Thread-safe through reference counting
@@ -216,6 +276,24 @@ extern void bytes_swap(bytes_t *a, bytes_t *b);
 /// @note Invalid BASE64 input will return an empty array
 extern bytes_t bytes_base64_decode(const bstr_t *str);
 
+/// @brief Get the maximum length of the data decoded from a BASE64 string.
+/// @param [in] str The BASE64 encoded string
+/// @return Upper bound of the decoded length, exact for valid padded input
+/// @note Computed from the input length and padding only, the input is not validated
+/// @note Returns 0 if the length is not a multiple of 4, as unpadded input is rejected
+extern size_t bytes_base64_decoded_len(const bstr_t *str);
+
+/// @brief Decode a BASE64 string into a caller-provided buffer.
+/// @param [in] str The BASE64 encoded string to decode
+/// @param [out] dst The destination buffer
+/// @param [in] dst_cap Capacity of the destination buffer in bytes
+/// @param [out] ok Set to false if the input is invalid or the buffer is too small
+/// @return Number of bytes written, or 0 on failure
+/// @note A buffer of bytes_base64_decoded_len() bytes is always large enough
+/// @note The input must be padded to a multiple of 4 characters
+/// @note No memory allocation occurs
+extern size_t bytes_base64_decode_into(const bstr_t *str, void *dst, size_t dst_cap, bool *ok);
+
 /// @brief Create a string containing BASE64 encoded data.
 /// @param [in] bytes The bytes object to encode
 /// @return New string containing the BASE64 encoded data
@@ -224,6 +302,424 @@ extern bytes_t bytes_base64_decode(const bstr_t *str);
 /// @note The encoding uses standard BASE64 alphabet
 extern bstr_t bytes_base64_encode(const bytes_t *bytes);
 
+/// @brief Get the length of the BASE64 encoding of a byte array.
+/// @param [in] bytes The bytes object to be encoded
+/// @return Number of BASE64 characters including padding, excluding any null terminator
+extern size_t bytes_base64_encoded_len(const bytes_t *bytes);
+
+/// @brief Encode a byte array as BASE64 into a caller-provided buffer.
+/// @param [in] bytes The bytes object to encode
+/// @param [out] dst The destination buffer
+/// @param [in] dst_cap Capacity of the destination buffer in bytes
+/// @return Number of BASE64 characters needed, as returned by bytes_base64_encoded_len()
+/// @note Nothing is written if the return value is greater than dst_cap
+/// @note No null terminator is written and no memory allocation occurs
+extern size_t bytes_base64_encode_into(const bytes_t *bytes, char *dst, size_t dst_cap);
+
+/// @brief Encode a byte array to BASE64 in pieces, passing each piece to a callback.
+/// @param [in] bytes The source bytes object
+/// @param [in] chunk_bytes Number of input bytes per piece, rounded down to a multiple of 3
+///                         (at least 3)
+/// @param [in] cb Callback invoked with each encoded piece and the user pointer
+/// @param [in] user User pointer passed through to the callback
+/// @note Concatenating the pieces gives the same result as bytes_base64_encode()
+/// @note Only the last piece can contain '=' padding
+/// @note Each piece is valid only during the callback
+extern void bytes_base64_encode_chunked(const bytes_t *bytes, size_t chunk_bytes,
+                                        void (*cb)(const bstr_t *chunk, void *user), void *user);
+
+/// @brief Create a byte array with the bytes of another one in reverse order.
+/// @param [in] bytes The source bytes object
+/// @return New bytes object containing the reversed data
+/// @note Creates a new reference-counted array with a single allocation
+extern bytes_t bytes_reverse(const bytes_t *bytes);
+
+/// @brief XOR two byte arrays byte by byte.
+/// @param [in] a The first bytes object
+/// @param [in] b The second bytes object
+/// @return New bytes object containing `a[i] ^ b[i]`
+/// @note The result has the length of the shorter operand, the longer one is not cycled
+/// @note A null operand counts as empty, so the result is empty
+extern bytes_t bytes_xor(const bytes_t *a, const bytes_t *b);
+
+/// @brief Iterate over successive fixed-size chunks of a byte array.
+/// @param [in] bytes The source bytes object
+/// @param [in] chunk_size Number of bytes per chunk, the last chunk may be shorter
+/// @param [in] cb Callback invoked with each chunk and the user pointer
+/// @param [in] user User pointer passed through to the callback
+/// @note Each chunk is a zero-copy view valid only during the callback
+/// @note Use bytes_clone() to keep a chunk after the callback returns
+/// @note Does nothing if chunk_size is 0
+extern void bytes_for_each_chunk(const bytes_t *bytes, size_t chunk_size,
+                                 void (*cb)(const bytes_t *chunk, void *user), void *user);
+
+/// @brief Iterate over the full-size chunks of a byte array, skipping the remainder.
+/// @param [in] bytes The source bytes object
+/// @param [in] chunk_size Number of bytes per chunk
+/// @param [in] cb Callback invoked with each chunk and the user pointer
+/// @param [in] user User pointer passed through to the callback
+/// @return Length of the trailing partial chunk that was not passed to the callback
+/// @note Each chunk is a zero-copy view valid only during the callback
+/// @note If chunk_size is 0 no chunks are produced and the whole length is returned
+extern size_t bytes_for_each_chunk_exact(const bytes_t *bytes, size_t chunk_size,
+                                         void (*cb)(const bytes_t *chunk, void *user),
+                                         void *user);
+
+/// @brief Split a byte array on every occurrence of a delimiter byte.
+/// @param [in] bytes The source bytes object
+/// @param [in] delim The delimiter byte
+/// @param [out] out Receives an allocated array of the pieces, or NULL if there are none
+/// @return Number of pieces, which is the number of delimiters plus one
+/// @note Each piece is a zero-copy view sharing the source allocation
+/// @note The array must be freed by \ref bytes_split_free() with the returned count
+/// @note A null source yields no pieces, an empty one yields a single empty piece
+extern size_t bytes_split_by_byte(const bytes_t *bytes, uint8_t delim, bytes_t **out);
+
+/// @brief Free an array of byte arrays returned by a split function.
+/// @param [in] arr The array to free
+/// @param [in] count Number of elements as returned by the split function
+/// @note Releases every element and then the array itself
+extern void bytes_split_free(bytes_t *arr, size_t count);
+
+/// @brief Strip leading and trailing ASCII whitespace from a byte array.
+/// @param [in] bytes The source bytes object
+/// @return New bytes object representing the trimmed range
+/// @note Zero-copy operation - shares data with source
+/// @note Whitespace is space, tab, CR, LF, FF and VT, no UTF-8 decoding is performed
+extern bytes_t bytes_trim_ascii(const bytes_t *bytes);
+
+/// @brief Find the offsets of all occurrences of a pattern in a byte array.
+/// @param [in] haystack The bytes object to search in
+/// @param [in] needle The pattern to search for
+/// @param [out] out Receives an allocated array of byte offsets, or NULL if there are none
+/// @return Number of matches
+/// @note Matches are non-overlapping and reported in ascending order
+/// @note An empty or null needle matches nothing
+/// @note The array must be freed by \ref bytes_usize_array_free() with the returned count
+extern size_t bytes_find_all(const bytes_t *haystack, const bytes_t *needle, size_t **out);
+
+/// @brief Free an array of offsets returned by this library.
+/// @param [in] arr The array to free
+/// @param [in] count Number of elements as returned by the producing function
+extern void bytes_usize_array_free(size_t *arr, size_t count);
+
+/// @brief Compare a byte array with a raw memory buffer.
+/// @param [in] bytes The bytes object
+/// @param [in] data Pointer to the buffer to compare with
+/// @param [in] len Number of bytes in the buffer
+/// @return true if the contents are identical
+/// @note A null bytes object or buffer counts as empty
+extern bool bytes_equals_slice(const bytes_t *bytes, const void *data, size_t len);
+
+/// @brief Create a byte array by repeating another one `n` times.
+/// @param [in] bytes The source bytes object
+/// @param [in] n Number of repetitions
+/// @return New bytes object containing the concatenated copies
+/// @note Performs a single allocation
+/// @note Returns an empty array if n is 0 or the total length would overflow
+extern bytes_t bytes_repeat(const bytes_t *bytes, size_t n);
+
+/// @brief Create a byte array filled with cryptographically secure random bytes.
+/// @param [in] len Number of random bytes
+/// @return New bytes object, or an empty one if the OS random number generator fails
+/// @note Requires the `rand` feature
+extern bytes_t bytes_random(size_t len);
+
+/// @brief Get the length of the common prefix of two byte arrays.
+/// @param [in] a The first bytes object
+/// @param [in] b The second bytes object
+/// @return Number of leading bytes that are equal in both arrays
+extern size_t bytes_common_prefix_len(const bytes_t *a, const bytes_t *b);
+
+/// @brief Format a byte buffer like the output of `hexdump -C`.
+/// @param [in] bytes The bytes object
+/// @param [in] width Number of bytes per line, 0 for the default of 16
+/// @return A string with an offset column, the hex bytes and an ASCII gutter on each line
+/// @note Non-printable bytes are shown as '.' in the ASCII gutter
+/// @note Unlike hexdump, repeated lines are not collapsed into '*'
+extern bstr_t bytes_hexdump(const bytes_t *bytes, size_t width);
+
+/// @brief Remove the first byte of a byte array.
+/// @param [in,out] bytes The bytes object, advanced by one byte on success
+/// @param [out] out_byte Receives the removed byte (may be null)
+/// @return true on success, false if the array is null or empty
+/// @note The remaining array shares the original buffer, no data is copied
+extern bool bytes_split_first(bytes_t *bytes, uint8_t *out_byte);
+
+/// @brief Remove the last byte of a byte array.
+/// @param [in,out] bytes The bytes object, shortened by one byte on success
+/// @param [out] out_byte Receives the removed byte (may be null)
+/// @return true on success, false if the array is null or empty
+/// @note The remaining array shares the original buffer, no data is copied
+extern bool bytes_split_last(bytes_t *bytes, uint8_t *out_byte);
+
+/// @brief Read an unsigned LEB128 varint from the front of a byte array.
+/// @param [in,out] bytes The bytes object, advanced past the varint on success
+/// @param [out] out Receives the decoded value (may be null)
+/// @return true on success, false if the varint is truncated or does not fit in 64 bits
+/// @note This is the protobuf varint encoding, a u64 takes at most 10 bytes
+/// @note On failure the array is left unchanged
+extern bool bytes_read_uvarint(bytes_t *bytes, uint64_t *out);
+
+/// @brief Encode a value as an unsigned LEB128 varint.
+/// @param [in] v The value to encode
+/// @return New byte array of 1 to 10 bytes holding the varint
+extern bytes_t bytes_encode_uvarint(uint64_t v);
+
+/// @brief Compute a delta that turns one byte array into another.
+/// @param [in] base The base byte array both sides already have
+/// @param [in] target The byte array to reproduce
+/// @return New byte array holding the patch, to be applied with bytes_apply_patch()
+/// @note The patch is the uvarint length of target followed by instructions, each starting with a
+///       uvarint tag: `len << 1` followed by a uvarint offset copies len bytes of base, and
+///       `len << 1 | 1` followed by len raw bytes inserts them
+/// @note Matches are found on 16-byte blocks of base with a rolling hash, so changes smaller
+///       than a block between matches are sent as inserts
+/// @note An empty or unrelated base yields a single insert of target
+extern bytes_t bytes_diff(const bytes_t *base, const bytes_t *target);
+
+/// @brief Rebuild a byte array from its base and a patch made by bytes_diff().
+/// @param [in] base The base byte array the patch was computed against
+/// @param [in] patch The patch
+/// @param [out] ok Set to true on success, false if the patch is malformed or does not fit base
+///                 (may be null)
+/// @return New byte array equal to the target passed to bytes_diff(), or an empty array on failure
+/// @note Copies outside base, truncated instructions and a length mismatch are all rejected
+extern bytes_t bytes_apply_patch(const bytes_t *base, const bytes_t *patch, bool *ok);
+
+/// @brief Read a byte from the front of a byte array.
+/// @param [in,out] bytes The bytes object, advanced by one byte on success
+/// @param [out] out Receives the value (may be null)
+/// @return true on success, false if the array is empty
+extern bool bytes_read_u8(bytes_t *bytes, uint8_t *out);
+
+/// @brief Read a big-endian 16-bit integer from the front of a byte array.
+/// @param [in,out] bytes The bytes object, advanced by 2 bytes on success
+/// @param [out] out Receives the value (may be null)
+/// @return true on success, false if fewer than 2 bytes remain, in which case nothing is consumed
+extern bool bytes_read_u16_be(bytes_t *bytes, uint16_t *out);
+
+/// @brief Read a little-endian 16-bit integer from the front of a byte array.
+/// @param [in,out] bytes The bytes object, advanced by 2 bytes on success
+/// @param [out] out Receives the value (may be null)
+/// @return true on success, false if fewer than 2 bytes remain, in which case nothing is consumed
+extern bool bytes_read_u16_le(bytes_t *bytes, uint16_t *out);
+
+/// @brief Read a big-endian 32-bit integer from the front of a byte array.
+/// @param [in,out] bytes The bytes object, advanced by 4 bytes on success
+/// @param [out] out Receives the value (may be null)
+/// @return true on success, false if fewer than 4 bytes remain, in which case nothing is consumed
+extern bool bytes_read_u32_be(bytes_t *bytes, uint32_t *out);
+
+/// @brief Read a little-endian 32-bit integer from the front of a byte array.
+/// @param [in,out] bytes The bytes object, advanced by 4 bytes on success
+/// @param [out] out Receives the value (may be null)
+/// @return true on success, false if fewer than 4 bytes remain, in which case nothing is consumed
+extern bool bytes_read_u32_le(bytes_t *bytes, uint32_t *out);
+
+/// @brief Read a big-endian 64-bit integer from the front of a byte array.
+/// @param [in,out] bytes The bytes object, advanced by 8 bytes on success
+/// @param [out] out Receives the value (may be null)
+/// @return true on success, false if fewer than 8 bytes remain, in which case nothing is consumed
+extern bool bytes_read_u64_be(bytes_t *bytes, uint64_t *out);
+
+/// @brief Read a little-endian 64-bit integer from the front of a byte array.
+/// @param [in,out] bytes The bytes object, advanced by 8 bytes on success
+/// @param [out] out Receives the value (may be null)
+/// @return true on success, false if fewer than 8 bytes remain, in which case nothing is consumed
+extern bool bytes_read_u64_le(bytes_t *bytes, uint64_t *out);
+
+/// @brief Read a byte at an offset without consuming it.
+/// @param [in] bytes The bytes object
+/// @param [in] offset Byte offset to read at
+/// @param [out] out Receives the value (may be null)
+/// @return true on success, false if offset is out of range
+extern bool bytes_peek_u8(const bytes_t *bytes, size_t offset, uint8_t *out);
+
+/// @brief Read a big-endian 16-bit integer at an offset without consuming it.
+/// @param [in] bytes The bytes object
+/// @param [in] offset Byte offset to read at
+/// @param [out] out Receives the value (may be null)
+/// @return true on success, false if fewer than 2 bytes are available at offset
+extern bool bytes_peek_u16_be(const bytes_t *bytes, size_t offset, uint16_t *out);
+
+/// @brief Read a little-endian 16-bit integer at an offset without consuming it.
+/// @param [in] bytes The bytes object
+/// @param [in] offset Byte offset to read at
+/// @param [out] out Receives the value (may be null)
+/// @return true on success, false if fewer than 2 bytes are available at offset
+extern bool bytes_peek_u16_le(const bytes_t *bytes, size_t offset, uint16_t *out);
+
+/// @brief Read a big-endian 32-bit integer at an offset without consuming it.
+/// @param [in] bytes The bytes object
+/// @param [in] offset Byte offset to read at
+/// @param [out] out Receives the value (may be null)
+/// @return true on success, false if fewer than 4 bytes are available at offset
+extern bool bytes_peek_u32_be(const bytes_t *bytes, size_t offset, uint32_t *out);
+
+/// @brief Read a little-endian 32-bit integer at an offset without consuming it.
+/// @param [in] bytes The bytes object
+/// @param [in] offset Byte offset to read at
+/// @param [out] out Receives the value (may be null)
+/// @return true on success, false if fewer than 4 bytes are available at offset
+extern bool bytes_peek_u32_le(const bytes_t *bytes, size_t offset, uint32_t *out);
+
+/// @brief Check whether a byte array holds valid UTF-8.
+/// @param [in] bytes The bytes object
+/// @return true if the data is valid UTF-8, including when it is null or empty
+/// @note Validates in place without allocating or creating a string
+extern bool bytes_is_valid_utf8(const bytes_t *bytes);
+
+/// @brief Estimate the Shannon entropy of a byte array.
+/// @param [in] bytes The bytes object
+/// @return Entropy in bits per byte, from 0.0 to 8.0
+/// @note Computed from the histogram of byte values, so it ignores ordering
+/// @note Returns 0.0 for a null or empty array
+extern double bytes_shannon_entropy(const bytes_t *bytes);
+
+/// @brief Consume the leading bytes that belong to a set.
+/// @param [in,out] bytes The bytes object, advanced past the consumed bytes
+/// @param [in] set The bytes that may be consumed, in any order
+/// @return New bytes object with the consumed run, sharing data with the source
+/// @note Consumes nothing if set is null or empty
+extern bytes_t bytes_take_while_in_set(bytes_t *bytes, const bytes_t *set);
+
+/// @brief Format a byte array as the body of a C array initializer.
+/// @param [in] bytes The bytes object
+/// @param [in] per_line Number of values per line, 0 for the default of 12
+/// @return A string like "0x00, 0x01,\n0x02" with no leading indentation or trailing newline
+/// @note Use bstr_indent() to indent the lines
+extern bstr_t bytes_to_c_array(const bytes_t *bytes, size_t per_line);
+
+/// @brief Rotate a byte array to the left.
+/// @param [in] bytes The source bytes object
+/// @param [in] mid Offset of the byte that becomes the first one, clamped to the length
+/// @return New byte array holding the bytes from mid onward followed by the bytes before mid
+/// @note Returns a shared reference to the source if mid is 0 or not less than the length
+extern bytes_t bytes_rotate_left(const bytes_t *bytes, size_t mid);
+
+/// @brief Collapse runs of identical adjacent bytes into a single byte.
+/// @param [in] bytes The source bytes object
+/// @return New byte array without consecutive duplicates, like `uniq` applied to bytes
+/// @note Bytes that are equal but not adjacent are all kept
+extern bytes_t bytes_dedup_adjacent(const bytes_t *bytes);
+
+/// @brief Count the run of a given byte at the start of a byte array.
+/// @param [in] bytes The bytes object
+/// @param [in] byte The byte value to count
+/// @return Number of leading bytes equal to byte
+/// @note Use with bytes_suffix() to strip leading zeros from a big-endian integer
+extern size_t bytes_count_leading(const bytes_t *bytes, uint8_t byte);
+
+/// @brief Count the run of a given byte at the end of a byte array.
+/// @param [in] bytes The bytes object
+/// @param [in] byte The byte value to count
+/// @return Number of trailing bytes equal to byte
+extern size_t bytes_count_trailing(const bytes_t *bytes, uint8_t byte);
+
+/// @brief Keep only the bytes of a byte array accepted by a predicate.
+/// @param [in] bytes The source bytes object
+/// @param [in] pred Callback invoked with each byte and the user pointer,
+///                  returns true to keep the byte
+/// @param [in] user User pointer passed through to the callback
+/// @return New byte array with the accepted bytes in their original order
+/// @note A null predicate keeps every byte
+extern bytes_t bytes_retain(const bytes_t *bytes, bool (*pred)(uint8_t byte, void *user),
+                            void *user);
+
+/// @brief Format a byte array as a string of binary digits.
+/// @param [in] bytes The bytes object
+/// @param [in] sep Unicode scalar value inserted between bytes, or 0 for no separator
+/// @return A string of '0' and '1' characters, eight per byte with the most significant bit first
+/// @note An invalid sep is treated as 0
+extern bstr_t bytes_to_bitstring(const bytes_t *bytes, uint32_t sep);
+
+/// @brief Create a byte array holding a header followed by a body.
+/// @param [in] head The bytes to place first
+/// @param [in] body The bytes to place after head
+/// @return New byte array with a copy of head followed by a copy of body
+/// @note Performs a single allocation of the combined length
+extern bytes_t bytes_prepend(const bytes_t *head, const bytes_t *body);
+
+/// @brief Wrap externally owned memory in a byte array without copying it.
+/// @param [in] data Pointer to the memory, which must stay valid and unmodified until drop_cb runs
+/// @param [in] len Length of the memory in bytes
+/// @param [in] drop_cb Callback invoked with ctx once the last reference is released (may be null)
+/// @param [in] ctx User pointer passed to drop_cb
+/// @return New bytes object viewing the external memory
+/// @note Slices and clones share the memory and keep it alive as well
+/// @note drop_cb may run on whichever thread releases the last reference
+extern bytes_t bytes_from_owner(const void *data, size_t len, void (*drop_cb)(void *ctx),
+                                void *ctx);
+
+/// @brief Compare two byte arrays at the positions selected by a mask.
+/// @param [in] a The first bytes object
+/// @param [in] b The second bytes object
+/// @param [in] mask Bytes selecting the positions to compare, 0 to ignore a position
+/// @return true if a and b are equal wherever mask is nonzero
+/// @note Returns false unless a, b and mask all have the same length
+extern bool bytes_equals_masked(const bytes_t *a, const bytes_t *b, const bytes_t *mask);
+
+/// @brief Reverse the byte order of every fixed-size element of a byte array.
+/// @param [in] bytes The source bytes object
+/// @param [in] element_size Size of each element in bytes, such as 2, 4 or 8
+/// @return New byte array with every element converted between little and big endian
+/// @note A trailing partial element shorter than element_size is copied unchanged
+/// @note Returns a shared reference to the source if element_size is 0 or 1
+extern bytes_t bytes_swap_endian(const bytes_t *bytes, size_t element_size);
+
+/// @brief Get the largest byte value of a byte array.
+/// @param [in] bytes The bytes object
+/// @return The maximum byte value from 0 to 255, or -1 if the array is null or empty
+extern int bytes_max_byte(const bytes_t *bytes);
+
+/// @brief Get the smallest byte value of a byte array.
+/// @param [in] bytes The bytes object
+/// @return The minimum byte value from 0 to 255, or -1 if the array is null or empty
+extern int bytes_min_byte(const bytes_t *bytes);
+
+/// @brief Pad a byte array so that its length is a multiple of an alignment.
+/// @param [in] bytes The source bytes object
+/// @param [in] align The alignment in bytes, such as 4 or 8
+/// @param [in] pad The byte value to append
+/// @return New byte array with between 0 and align - 1 pad bytes appended
+/// @note Returns a shared reference to the source if it is already aligned or align is 0 or 1
+/// @note Returns an empty array if the padded length overflows or cannot be allocated
+extern bytes_t bytes_pad_to_align(const bytes_t *bytes, size_t align, uint8_t pad);
+
+/// @brief Read a byte array from a callback until end of input.
+/// @param [in] read_cb Callback that fills up to len bytes at buf and returns the number of
+///                     bytes written, 0 at end of input or a negative value on error
+/// @param [in] user User pointer passed through to the callback
+/// @param [in] hint Expected total size, used as the initial capacity
+/// @return New bytes object with everything read, or an empty array on error
+/// @note The data read before an error is discarded
+/// @note A return value greater than len is treated as an error
+extern bytes_t bytes_from_reader(intptr_t (*read_cb)(void *buf, size_t len, void *user),
+                                 void *user, size_t hint);
+
+/// @brief Compute the Fletcher-16 checksum of a byte array.
+/// @param [in] bytes The bytes object
+/// @return The checksum, with the second sum in the high byte
+/// @note A null array has the checksum of empty input, which is 0
+extern uint16_t bytes_fletcher16(const bytes_t *bytes);
+
+/// @brief Compute the Fletcher-32 checksum of a byte array.
+/// @param [in] bytes The bytes object
+/// @return The checksum, with the second sum in the high 16 bits
+/// @note The data is summed as little-endian 16-bit words, an odd final byte is padded with 0
+/// @note A null array has the checksum of empty input, which is 0
+extern uint32_t bytes_fletcher32(const bytes_t *bytes);
+
+/// @brief Decode a hexadecimal string and append the bytes to a byte array.
+/// @param [in,out] bytes The bytes object, extended with the decoded bytes
+/// @param [in] hex Pairs of hexadecimal digits in either case, such as "deadBEEF"
+/// @return true on success, false if hex has an odd length or a non-hex character
+/// @note On failure the array is left unchanged
+/// @note An array not shared with any other bytes object grows in place with amortized
+///       reallocation, a shared one is first copied, which costs O(len)
+extern bool bytes_append_hex(bytes_t *bytes, const bstr_t *hex);
+
 ////////////////////////////////////////////////////////////////////////////////
 
 /// @brief Initialize an empty string in place.
@@ -274,6 +770,27 @@ extern bstr_t bstr_from_static(const char *static_str, size_t len);
 /// @note Invalid UTF-8 input will return an empty string
 extern bstr_t bstr_from_bytes(const bytes_t *bytes);
 
+/// @brief Create a string from a bytes object, rejecting invalid UTF-8.
+/// @param [in] bytes The source bytes object containing UTF-8 data
+/// @param [out] ok Set to true on success, false if the data is not valid UTF-8 (may be null)
+/// @return New string object sharing the buffer of bytes, or an empty string on failure
+/// @note No data is copied, the string references the same buffer as the bytes object
+/// @note Unlike bstr_from_bytes(), invalid input is never replaced lossily
+extern bstr_t bstr_from_bytes_checked(const bytes_t *bytes, bool *ok);
+
+/// @brief Reinterpret a bytes object as a string without validating it.
+/// @param [in] bytes The source bytes object, which must contain valid UTF-8
+/// @return New string object sharing the buffer of bytes
+/// @note No data is copied and no UTF-8 validation is performed
+/// @note Unsafe by contract: passing data that is not valid UTF-8 is undefined behavior
+extern bstr_t bytes_into_bstr_unchecked(const bytes_t *bytes);
+
+/// @brief Get the UTF-8 data of a string as a bytes object.
+/// @param [in] s The source string object
+/// @return New bytes object sharing the buffer of s
+/// @note No data is copied, only the reference count is incremented
+extern bytes_t bstr_to_bytes(const bstr_t *s);
+
 /// @brief Create a string by copying from a UTF-8 string.
 /// @param [in] str The source UTF-8 string to copy from
 /// @param [in] len Length of string in bytes, or NPOS to calculate from null terminator
@@ -331,35 +848,536 @@ extern void bstr_swap(bstr_t *a, bstr_t *b);
 /// @param [in,out] s The source string object
 /// @return New buffer containing null-terminated UTF-8 data
 /// @note Creates a new independent copy with null terminator
-/// @note Caller must free the returned buffer using bstr_mem_free() or free()
+/// @note Caller must free the returned buffer using bstr_mem_free()
 /// @note Thread-safe for concurrent reads
+/// @note Embedded NUL characters (U+0000) truncate the string as seen by C string functions,
+///       use bstr_dup_utf8_checked() to get the real length
 extern char *bstr_dup_utf8(const bstr_t *s);
 
+/// @brief Create a null-terminated UTF-8 string copy and report its real length.
+/// @param [in] s The source string object
+/// @param [out] out_len Receives the length in bytes excluding the null terminator, or 0 on failure
+/// @return New buffer containing null-terminated UTF-8 data
+/// @note The length accounts for embedded NUL characters
+/// @note Caller must free the returned buffer using bstr_mem_free()
+extern char *bstr_dup_utf8_checked(const bstr_t *s, size_t *out_len);
+
 /// @brief Create a null-terminated UTF-16 string copy.
 /// @param [in,out] s The source string object
 /// @return New buffer containing null-terminated UTF-16 data
 /// @note Creates a new independent copy with null terminator
-/// @note Caller must free the returned buffer using bstr_mem_free() or free()
+/// @note Caller must free the returned buffer using bstr_mem_free()
 /// @note Thread-safe for concurrent reads
 /// @note Handles conversion from UTF-8 to UTF-16 encoding
+/// @note Returns NULL if allocation fails or the buffer size would overflow
 extern uint16_t *bstr_dup_utf16(const bstr_t *s);
 
 /// @brief Create a null-terminated UTF-32 string copy.
 /// @param [in,out] s The source string object
 /// @return New buffer containing null-terminated UTF-32 data
 /// @note Creates a new independent copy with null terminator
-/// @note Caller must free the returned buffer using bstr_mem_free() or free()
+/// @note Caller must free the returned buffer using bstr_mem_free()
 /// @note Thread-safe for concurrent reads
 /// @note Handles conversion from UTF-8 to UTF-32 encoding
+/// @note Returns NULL if allocation fails or the buffer size would overflow
 extern uint32_t *bstr_dup_utf32(const bstr_t *s);
 
-/// @brief Free memory allocated by string duplication functions.
-/// @param [in] ptr Pointer to memory allocated by bstr_dup_* functions
+/// @brief Convert a path to a null-terminated UTF-16 string for the Win32 `W` APIs.
+/// @param [in] s The source path
+/// @return New buffer containing the null-terminated UTF-16 path, or NULL on failure
+/// @note Absolute drive (C:\...) and UNC (\\server\share) paths of 248 UTF-16 units or more,
+///       the directory limit, get the verbatim prefix \\?\ or \\?\UNC\ and have '/' replaced
+///       by '\'
+/// @note Verbatim paths bypass Win32 normalization, so a path is only prefixed if it is already
+///       normalized: no repeated separators, no "." or ".." components and no component ending
+///       in a dot or a space
+/// @note Shorter, relative, unnormalized and already prefixed paths are converted unchanged,
+///       use GetFullPathNameW() first to make such paths eligible
+/// @note Caller must free the returned buffer using bstr_mem_free()
+extern uint16_t *bstr_to_wide_path(const bstr_t *s);
+
+/// @brief Free a C buffer returned by this library.
+/// @param [in] ptr Pointer to memory allocated by bstr_dup_*, bstr_to_wide_path() or
+///                 bytes_into_c_buffer()
 /// @note Thread-safe memory deallocation
-/// @note Equivalent to standard free() function
+/// @note Equivalent to standard free() function unless bstr_set_allocator() is used
 /// @note Sets freed pointer to NULL
 extern void bstr_mem_free(void *ptr);
 
+/// @brief Set the allocator used for the C buffers returned by this library.
+/// @param [in] malloc_fn Replacement of malloc()
+/// @param [in] free_fn Replacement of free()
+/// @param [in] realloc_fn Replacement of realloc()
+/// @note Applies to the buffers returned by bstr_dup_utf8(), bstr_dup_utf8_checked(),
+///       bstr_dup_utf16(), bstr_dup_utf32(), bstr_to_wide_path() and bytes_into_c_buffer(),
+///       and to bstr_mem_free()
+/// @note If any of the functions is NULL, the libc allocator is restored
+/// @note The three functions are installed together, a concurrent allocation sees either the old
+///       or the new set, never a mix
+/// @note Must be called before any buffer is allocated: freeing a buffer with a different
+///       allocator than the one that allocated it is undefined behavior
+/// @note Each call keeps a small record alive for the rest of the process
+extern void bstr_set_allocator(void *(*malloc_fn)(size_t size), void (*free_fn)(void *ptr),
+                               void *(*realloc_fn)(void *ptr, size_t size));
+
+/// @brief Escape a string for embedding between JSON double quotes.
+/// @param [in] s The source string object
+/// @return New string with `"`, `\` and control characters escaped
+/// @note Control characters without a short form are written as \\uXXXX
+/// @note Non-ASCII characters are kept as UTF-8
+/// @note Returns a shared reference to the source if nothing needs escaping
+extern bstr_t bstr_json_escape(const bstr_t *s);
+
+/// @brief Escape a string for JSON, also escaping all non-ASCII characters.
+/// @param [in] s The source string object
+/// @return New pure-ASCII string suitable for embedding between JSON double quotes
+/// @note Characters outside the BMP are written as UTF-16 surrogate pairs
+/// @note Returns a shared reference to the source if nothing needs escaping
+extern bstr_t bstr_json_escape_ascii(const bstr_t *s);
+
+/// @brief Iterate over the whitespace-separated words of a string.
+/// @param [in] s The source string object
+/// @param [in] cb Callback invoked with each word and the user pointer
+/// @param [in] user User pointer passed through to the callback
+/// @note Runs of Unicode whitespace are collapsed, leading/trailing whitespace is ignored
+/// @note Each word is a zero-copy view valid only during the callback
+/// @note Use bstr_clone() to keep a word after the callback returns
+extern void bstr_for_each_word(const bstr_t *s, void (*cb)(const bstr_t *word, void *user),
+                               void *user);
+
+/// @brief Iterate over the Unicode scalar values of a string.
+/// @param [in] s The source string object
+/// @param [in] cb Callback invoked with each scalar value, its byte offset and the user pointer
+/// @param [in] user User pointer passed through to the callback
+/// @note Walks the string once, O(n) in total
+extern void bstr_for_each_char(const bstr_t *s,
+                               void (*cb)(uint32_t scalar, size_t byte_offset, void *user),
+                               void *user);
+
+/// @brief Iterate over the Unicode scalar values of a string from the end.
+/// @param [in] s The source string object
+/// @param [in] cb Callback invoked with each scalar value, its byte offset and the user pointer
+/// @param [in] user User pointer passed through to the callback
+/// @note Walks the string once backwards, without reversing or copying it
+extern void bstr_for_each_char_rev(const bstr_t *s,
+                                   void (*cb)(uint32_t scalar, size_t byte_offset, void *user),
+                                   void *user);
+
+/// @brief Convert a string to a Unicode normalization form.
+/// @param [in] s The source string object
+/// @param [in] form One of BSTR_NFC, BSTR_NFD, BSTR_NFKC or BSTR_NFKD
+/// @return New string in the requested normalization form
+/// @note Canonically equivalent strings produce identical output after BSTR_NFC
+/// @note Returns a shared reference to the source if it is already normalized or form is unknown
+/// @note Requires the `unicode-normalization` feature
+extern bstr_t bstr_normalize(const bstr_t *s, int form);
+
+/// @brief Get the number of terminal columns a string occupies.
+/// @param [in] s The source string object
+/// @return Display width in columns
+/// @note Wide (e.g. CJK) characters count as 2, zero-width and combining characters as 0
+/// @note Requires the `unicode-width` feature
+extern size_t bstr_display_width(const bstr_t *s);
+
+/// @brief Get a prefix of a string containing at most `max_chars` characters.
+/// @param [in] s The source string object
+/// @param [in] max_chars Maximum number of Unicode scalar values to keep
+/// @return New string object representing the prefix
+/// @note Zero-copy operation - shares data with source
+/// @note Never splits a multi-byte character
+extern bstr_t bstr_truncate_chars(const bstr_t *s, size_t max_chars);
+
+/// @brief Truncate a string to at most `max_chars` characters, ending with "…" if truncated.
+/// @param [in] s The source string object
+/// @param [in] max_chars Maximum number of Unicode scalar values, including the ellipsis
+/// @return New string object, which is a shared reference to the source if no truncation occurred
+/// @note Never splits a multi-byte character
+extern bstr_t bstr_truncate_chars_ellipsis(const bstr_t *s, size_t max_chars);
+
+/// @brief Uppercase the first character of a string and lowercase the rest.
+/// @param [in] s The source string object
+/// @return New string object containing the capitalized text
+/// @note Uses Unicode case mapping, the byte length may change
+extern bstr_t bstr_capitalize(const bstr_t *s);
+
+/// @brief Capitalize every whitespace-separated word of a string.
+/// @param [in] s The source string object
+/// @return New string object containing the title-cased text
+/// @note Each word is capitalized like bstr_capitalize(), whitespace is preserved as-is
+/// @note Uses Unicode case mapping, the byte length may change
+extern bstr_t bstr_title_case(const bstr_t *s);
+
+/// @brief Compare a string with a null-terminated UTF-8 C string.
+/// @param [in] s The string object
+/// @param [in] cstr The null-terminated C string to compare with
+/// @return true if the contents are identical
+/// @note No memory allocation occurs
+/// @note A null string object or C string counts as empty
+extern bool bstr_equals_cstr(const bstr_t *s, const char *cstr);
+
+/// @brief Compare a string with a null-terminated C string, ignoring ASCII case.
+/// @param [in] s The string object
+/// @param [in] cstr The null-terminated C string to compare with
+/// @return true if the contents are equal after ASCII case folding
+/// @note Non-ASCII bytes are compared by raw value
+/// @note No memory allocation occurs
+/// @note A null string object or C string counts as empty
+extern bool bstr_equals_ignore_case_cstr(const bstr_t *s, const char *cstr);
+
+/// @brief Create a random token as a lowercase hexadecimal string.
+/// @param [in] bytes_len Number of random bytes, the string has twice as many characters
+/// @return New string object, or an empty one if the OS random number generator fails
+/// @note Uses cryptographically secure random bytes from bytes_random()
+/// @note Requires the `rand` feature
+extern bstr_t bstr_random_hex(size_t bytes_len);
+
+/// @brief Format a signed integer with a printf-style format string.
+/// @param [in] fmt Null-terminated UTF-8 format string with exactly one conversion specification
+/// @param [in] v The value to format
+/// @return New string object, or an empty string if the format string is rejected
+/// @note The specification is `%[flags][width][.precision]conv` with flags `-`, `0`, `+`, ` `, `#`
+/// @note Accepted conversions are `d`, `i`, `x`, `X`, `o` and `b` (binary), `%%` is a literal `%`
+/// @note Radix conversions print negative values as their 64-bit two's complement like C
+/// @note As in C, the `+` and ` ` flags are ignored by every conversion except `d` and `i`
+/// @note Length modifiers, `*` and conversions of other types are rejected
+/// @note Widths and precisions greater than 4096 are rejected
+extern bstr_t bstr_format_i64(const char *fmt, int64_t v);
+
+/// @brief Format an unsigned integer with a printf-style format string.
+/// @param [in] fmt Null-terminated UTF-8 format string with exactly one conversion specification
+/// @param [in] v The value to format
+/// @return New string object, or an empty string if the format string is rejected
+/// @note Same syntax as bstr_format_i64(), accepted conversions are `u`, `x`, `X`, `o` and `b`
+extern bstr_t bstr_format_u64(const char *fmt, uint64_t v);
+
+/// @brief Format a floating-point number with a printf-style format string.
+/// @param [in] fmt Null-terminated UTF-8 format string with exactly one conversion specification
+/// @param [in] v The value to format
+/// @return New string object, or an empty string if the format string is rejected
+/// @note Same syntax as bstr_format_i64(), accepted conversions are `f`, `e` and `E`
+/// @note The precision defaults to 6 digits after the decimal point
+extern bstr_t bstr_format_f64(const char *fmt, double v);
+
+/// @brief Convert a character index to a byte offset.
+/// @param [in] s The string object
+/// @param [in] char_index Index of a Unicode scalar value
+/// @return Byte offset where the character starts, or the byte length if char_index is out of range
+extern size_t bstr_char_to_byte(const bstr_t *s, size_t char_index);
+
+/// @brief Convert a byte offset to a character index.
+/// @param [in] s The string object
+/// @param [in] byte_index Byte offset into the UTF-8 data
+/// @return Index of the character containing the byte, or the character count if out of range
+/// @note An offset inside a multi-byte character maps to that character
+extern size_t bstr_byte_to_char(const bstr_t *s, size_t byte_index);
+
+/// @brief Check whether a string is empty or contains only whitespace.
+/// @param [in] s The string object
+/// @return true if the string is null, empty or all Unicode whitespace
+extern bool bstr_is_blank(const bstr_t *s);
+
+/// @brief Count the non-overlapping occurrences of a substring.
+/// @param [in] s The string to search in
+/// @param [in] needle The substring to search for
+/// @return Number of non-overlapping matches, scanning from the start
+/// @note An empty or null needle matches nothing and returns 0
+extern size_t bstr_count_matches(const bstr_t *s, const bstr_t *needle);
+
+/// @brief Get the length of the common prefix of two strings.
+/// @param [in] a The first string object
+/// @param [in] b The second string object
+/// @return Length in bytes, not characters, of the longest common prefix
+/// @note The result is rounded down to a character boundary, so it is a valid UTF-8 prefix of both
+extern size_t bstr_common_prefix_len(const bstr_t *a, const bstr_t *b);
+
+/// @brief Compute the Levenshtein edit distance between two strings.
+/// @param [in] a The first string object
+/// @param [in] b The second string object
+/// @return Minimum number of single-character insertions, deletions and substitutions
+/// @note Operates on Unicode scalar values, not bytes
+/// @note Uses O(min(len(a), len(b))) working memory
+extern size_t bstr_levenshtein(const bstr_t *a, const bstr_t *b);
+
+/// @brief Check whether a string contains any character from a set.
+/// @param [in] s The string to search in
+/// @param [in] chars The set of characters to look for
+/// @return true if any character of s also occurs in chars
+/// @note chars is treated as a set of Unicode scalar values, not as a substring
+/// @note An empty or null set matches nothing and returns false
+extern bool bstr_contains_any(const bstr_t *s, const bstr_t *chars);
+
+/// @brief Split a string on the first occurrence of a separator.
+/// @param [in] s The string to split
+/// @param [in] sep The separator to search for
+/// @param [out] left Receives the part before the separator
+/// @param [out] right Receives the part after the separator
+/// @return true if the separator was found, false otherwise
+/// @note Both parts share the buffer of s, no data is copied
+/// @note If the separator is not found, left and right are set to empty strings
+/// @note left and right are overwritten without being released and must each be released after use
+extern bool bstr_split_once(const bstr_t *s, const bstr_t *sep, bstr_t *left, bstr_t *right);
+
+/// @brief Split a string on the last occurrence of a separator.
+/// @param [in] s The string to split
+/// @param [in] sep The separator to search for
+/// @param [out] left Receives the part before the separator
+/// @param [out] right Receives the part after the separator
+/// @return true if the separator was found, false otherwise
+/// @note Same zero-copy and out-parameter contract as bstr_split_once()
+extern bool bstr_rsplit_once(const bstr_t *s, const bstr_t *sep, bstr_t *left, bstr_t *right);
+
+/// @brief Classify the content of a string in a single pass.
+/// @param [in] s The string object
+/// @return BSTR_CLASS_EMPTY if the string is null or empty, BSTR_CLASS_ASCII if it is pure
+///         ASCII, or BSTR_CLASS_UNICODE if it contains non-ASCII characters
+extern int bstr_classify(const bstr_t *s);
+
+/// @brief Match a string against a shell-style glob pattern.
+/// @param [in] s The string to test
+/// @param [in] pattern The glob pattern
+/// @return true if the whole string matches the pattern
+/// @note '*' matches any run of characters, '?' any single character, and [abc], [a-z] or
+///       [!a-z] (also [^a-z]) a character class; '\' escapes the following character
+/// @note Matching is anchored at both ends and operates on Unicode scalar values
+/// @note An unterminated '[' matches itself literally
+extern bool bstr_matches_glob(const bstr_t *s, const bstr_t *pattern);
+
+/// @brief Expand ${name} placeholders in a string.
+/// @param [in] s The template string
+/// @param [in] lookup Callback storing the value of the variable name in *out and returning true,
+///                    or returning false if the variable is unknown
+/// @param [in] user User pointer passed through to the callback
+/// @return New string with every placeholder replaced
+/// @note "$$" produces a literal '$', and a '$' not starting a complete placeholder is kept as is
+/// @note Placeholders of unknown variables are kept as is, return true with an empty *out to
+///       remove them instead
+/// @note *out holds an empty string on entry and may be overwritten without being released,
+///       bstr_expand() releases it afterwards whatever the callback returns
+/// @note A null callback treats every variable as unknown
+extern bstr_t bstr_expand(const bstr_t *s,
+                          bool (*lookup)(const bstr_t *name, void *user, bstr_t *out),
+                          void *user);
+
+/// @brief Split a string into lines.
+/// @param [in] s The source string object
+/// @param [out] out Receives an allocated array of the lines, or NULL if there are none
+/// @return Number of lines
+/// @note Lines end with "\n" or "\r\n", which are not included in the lines
+/// @note A trailing line terminator does not produce a final empty line, as in Rust's str::lines()
+/// @note Each line is a zero-copy view sharing the source allocation
+/// @note The array must be freed by \ref bstr_split_free() with the returned count
+extern size_t bstr_lines(const bstr_t *s, bstr_t **out);
+
+/// @brief Free an array of strings returned by a split function.
+/// @param [in] arr The array to free
+/// @param [in] count Number of elements as returned by the split function
+/// @note Releases every element and then the array itself
+extern void bstr_split_free(bstr_t *arr, size_t count);
+
+/// @brief Word-wrap a string to a maximum display width.
+/// @param [in] s The source string object
+/// @param [in] width Maximum number of terminal columns per line, 0 to disable wrapping
+/// @return New string with newlines inserted so that no line is wider than width
+/// @note Lines are broken at whitespace, and words wider than width are broken between characters,
+///       never before a combining mark, variation selector or ZWJ, nor after a ZWJ
+/// @note A single character wider than width is put on a line of its own
+/// @note Existing line terminators, "\n" or "\r\n", are kept as paragraph breaks, and the lines
+///       inserted within a paragraph end with the same terminator
+/// @note The leading spaces and tabs of a paragraph are kept if its first word fits after them,
+///       and are dropped otherwise
+/// @note Any other run of whitespace collapses to a single space
+/// @note Widths are measured as by bstr_display_width()
+/// @note Requires the `unicode-width` feature
+extern bstr_t bstr_wrap(const bstr_t *s, size_t width);
+
+/// @brief Word-wrap a string to a maximum display width, hyphenating over-long words.
+/// @param [in] s The source string object
+/// @param [in] width Maximum number of terminal columns per line, 0 to disable wrapping
+/// @return New string with newlines inserted so that no line is wider than width
+/// @note Same as bstr_wrap(), except that a word wider than width is broken with a trailing '-'
+/// @note The hyphen is a plain ASCII '-' inserted at the break point, there is no dictionary
+/// @note With a width of 1 words are broken without hyphens
+/// @note Requires the `unicode-width` feature
+extern bstr_t bstr_wrap_hyphenate(const bstr_t *s, size_t width);
+
+/// @brief Compare a byte range of one string with the whole of another.
+/// @param [in] a The string containing the range
+/// @param [in] a_start Byte offset where the range starts
+/// @param [in] a_len Length of the range in bytes
+/// @param [in] b The string to compare the range with
+/// @return true if the bytes of the range equal the bytes of b
+/// @note The range is clamped to the length of a
+/// @note No intermediate string is created
+extern bool bstr_eq_range(const bstr_t *a, size_t a_start, size_t a_len, const bstr_t *b);
+
+/// @brief Insert a prefix at the start of every line of a string.
+/// @param [in] s The source string object
+/// @param [in] prefix The prefix to insert, typically spaces
+/// @return New string with every line prefixed
+/// @note Empty lines are prefixed as well, except the empty line after a trailing newline
+/// @note Returns a shared reference to the source string if prefix is null or empty
+extern bstr_t bstr_indent(const bstr_t *s, const bstr_t *prefix);
+
+/// @brief Remove the common leading whitespace from every line of a string.
+/// @param [in] s The source string object
+/// @return New string with the longest common prefix of spaces and tabs removed
+/// @note Lines that are empty or contain only whitespace are ignored when computing the prefix,
+///       and are reduced to their line terminator in the result
+/// @note Spaces and tabs are not considered equal to each other
+extern bstr_t bstr_dedent(const bstr_t *s);
+
+/// @brief Check whether a string starts with a prefix, ignoring ASCII case.
+/// @param [in] s The string object
+/// @param [in] prefix The prefix to look for
+/// @return true if s starts with prefix, or if prefix is null
+/// @note Only ASCII letters are case-folded, other bytes must match exactly
+extern bool bstr_starts_with_ignore_case(const bstr_t *s, const bstr_t *prefix);
+
+/// @brief Check whether a string ends with a suffix, ignoring ASCII case.
+/// @param [in] s The string object
+/// @param [in] suffix The suffix to look for
+/// @return true if s ends with suffix, or if suffix is null
+/// @note Only ASCII letters are case-folded, other bytes must match exactly
+extern bool bstr_ends_with_ignore_case(const bstr_t *s, const bstr_t *suffix);
+
+/// @brief Convert an identifier to snake_case.
+/// @param [in] s The source identifier
+/// @return New string with the words in lower case joined by '_'
+/// @note Words are split at '_', '-', whitespace and camelCase humps,
+///       so "HTTPServer" becomes "http_server"
+extern bstr_t bstr_to_snake_case(const bstr_t *s);
+
+/// @brief Convert an identifier to camelCase.
+/// @param [in] s The source identifier
+/// @return New string with the first word in lower case and the others capitalized
+/// @note Words are split as by bstr_to_snake_case(), so "http_server" becomes "httpServer"
+extern bstr_t bstr_to_camel_case(const bstr_t *s);
+
+/// @brief Remove every occurrence of a character from a string.
+/// @param [in] s The source string object
+/// @param [in] ch The Unicode scalar value to remove
+/// @return New string without the character
+/// @note Returns a shared reference to the source if ch does not occur or is not a valid scalar
+extern bstr_t bstr_remove_char(const bstr_t *s, uint32_t ch);
+
+/// @brief Keep only the characters of a string accepted by a predicate.
+/// @param [in] s The source string object
+/// @param [in] pred Callback invoked with each scalar value and the user pointer,
+///                  returns true to keep the character
+/// @param [in] user User pointer passed through to the callback
+/// @return New string with the accepted characters in their original order
+/// @note A null predicate keeps every character
+extern bstr_t bstr_retain(const bstr_t *s, bool (*pred)(uint32_t scalar, void *user), void *user);
+
+/// @brief Insert a separator between groups of digits of a number.
+/// @param [in] s The source string, such as "-1234567.89"
+/// @param [in] group_size Number of digits per group, counted from the right
+/// @param [in] sep The Unicode scalar value to insert, such as ','
+/// @return New string with separators in the integer part, such as "-1,234,567.89"
+/// @note Only the leading run of ASCII digits after an optional '-' or '+' sign is grouped,
+///       anything after it, such as a fraction, is copied unchanged
+/// @note Returns a shared reference to the source if group_size is 0 or sep is not a valid scalar
+extern bstr_t bstr_group_digits(const bstr_t *s, size_t group_size, uint32_t sep);
+
+/// @brief Parse a boolean value.
+/// @param [in] s The string to parse
+/// @param [out] ok Set to true if s was recognized, false otherwise (may be null)
+/// @return true for "true", "yes", "on" or "1", false for anything else
+/// @note Recognizes "false", "no", "off" and "0" as false, all comparisons ignore ASCII case
+/// @note Surrounding whitespace is not accepted
+extern bool bstr_parse_bool(const bstr_t *s, bool *ok);
+
+/// @brief Compare at most the first n bytes of two strings, like strncmp().
+/// @param [in] a The first string object
+/// @param [in] b The second string object
+/// @param [in] n Maximum number of bytes to compare
+/// @return -1, 0 or 1 if the compared prefix of a is less than, equal to or greater than that of b
+/// @note Bytes are compared as unsigned values, a null string compares as empty
+/// @note n is not rounded to a character boundary
+extern int bstr_ncompare(const bstr_t *a, const bstr_t *b, size_t n);
+
+/// @brief Center a string within a given number of characters.
+/// @param [in] s The source string object
+/// @param [in] total_chars Width of the result in Unicode scalar values
+/// @param [in] pad The Unicode scalar value to pad with, such as ' '
+/// @return New string with padding on both sides, the extra character going on the right
+/// @note Returns a shared reference to the source if it is already at least total_chars long
+///       or pad is not a valid scalar
+extern bstr_t bstr_center(const bstr_t *s, size_t total_chars, uint32_t pad);
+
+/// @brief Compare two strings, treating every run of whitespace as a single space.
+/// @param [in] a The first string object
+/// @param [in] b The second string object
+/// @return true if the strings are equal apart from the amount and kind of whitespace
+/// @note Leading and trailing whitespace is ignored, and Unicode whitespace is recognized
+/// @note Whitespace is never equal to no whitespace, so "a b" and "ab" differ
+extern bool bstr_eq_ignore_whitespace(const bstr_t *a, const bstr_t *b);
+
+/// @brief Split a string into maximal runs of characters of the same class.
+/// @param [in] s The source string object
+/// @param [in] cb Callback invoked with each token, its BSTR_TOKEN_* class and the user pointer
+/// @param [in] user User pointer passed through to the callback
+/// @note Classes are BSTR_TOKEN_LETTER (alphabetic), BSTR_TOKEN_DIGIT (numeric),
+///       BSTR_TOKEN_SPACE (whitespace), BSTR_TOKEN_OTHER (control characters)
+///       and BSTR_TOKEN_PUNCT (everything else, such as punctuation and symbols)
+/// @note Each token is a zero-copy view valid only during the callback
+extern void bstr_for_each_token(const bstr_t *s,
+                                void (*cb)(const bstr_t *token, int cls, void *user), void *user);
+
+/// @brief Convert a string to Latin-1 (ISO 8859-1) bytes.
+/// @param [in] s The source string object
+/// @param [in] replacement Byte substituted for characters above U+00FF, such as '?'
+/// @param [out] out Receives the Latin-1 bytes, one per character (may be null)
+/// @return Number of characters that were replaced
+/// @note out is overwritten without being released and must be released after use
+extern size_t bstr_to_latin1(const bstr_t *s, uint8_t replacement, bytes_t *out);
+
+/// @brief Create a string from Latin-1 (ISO 8859-1) data.
+/// @param [in] data The Latin-1 bytes, each one a code point from U+0000 to U+00FF
+/// @param [in] len Length of the data in bytes
+/// @return New string object with the data converted to UTF-8
+/// @note Never fails, bytes 0x80 to 0xFF become two-byte UTF-8 sequences
+extern bstr_t bstr_from_latin1(const void *data, size_t len);
+
+/// @brief Split a string after every occurrence of a separator, keeping the separators.
+/// @param [in] s The source string object
+/// @param [in] sep The separator, included at the end of each piece it terminates
+/// @param [in] cb Callback invoked with each piece and the user pointer
+/// @param [in] user User pointer passed through to the callback
+/// @note Concatenating the pieces gives back the original string, as with Rust's
+///       str::split_inclusive()
+/// @note An empty string produces no pieces, and an empty or null sep a single piece
+/// @note Each piece is a zero-copy view valid only during the callback
+extern void bstr_for_each_split_inclusive(const bstr_t *s, const bstr_t *sep,
+                                          void (*cb)(const bstr_t *piece, void *user),
+                                          void *user);
+
+/// @brief Wrap a string in quotes, escaping embedded quotes and backslashes.
+/// @param [in] s The source string object
+/// @param [in] quote_char The Unicode scalar value to quote with, such as '"' or '\''
+/// @return New string such as "a \"b\" c", or an empty string if quote_char is not a valid scalar
+/// @note Only quote_char and '\' are escaped, with a preceding '\'; use bstr_json_escape() for JSON
+///       control characters
+extern bstr_t bstr_quote(const bstr_t *s, uint32_t quote_char);
+
+/// @brief Get the byte offsets of every character boundary of a string.
+/// @param [in] s The source string object
+/// @param [out] out Receives an allocated array with the offset where each character starts,
+///                  followed by the byte length of the string
+/// @return Number of offsets, which is the character count plus one
+/// @note The array must be freed by \ref bytes_usize_array_free() with the returned count
+extern size_t bstr_char_boundaries(const bstr_t *s, size_t **out);
+
+/// @brief Compare two strings ignoring case and diacritics.
+/// @param [in] a The first string object
+/// @param [in] b The second string object
+/// @return true if the strings are equal after decomposition (NFD), case folding and removal
+///         of combining marks, so "Café" equals "CAFE" and "straße" equals "STRASSE"
+/// @note Case folding maps every character to uppercase and then to lowercase with the full
+///       Unicode mappings, which matches full case folding except that dotless 'ı' also equals 'i'
+/// @note Requires the `unicode-normalization` feature
+extern bool bstr_eq_loose(const bstr_t *a, const bstr_t *b);
+
 #if (WCHAR_MAX == 0xffff)
 #define bstr_from_wchar(str, len) bstr_from_utf16((const uint16_t *)(str), len)
 #define bstr_dup_wchar(s)         ((wchar_t *)bstr_dup_utf16(s))
@@ -368,6 +1386,192 @@ extern void bstr_mem_free(void *ptr);
 #define bstr_dup_wchar(s)         ((wchar_t *)bstr_dup_utf32(s))
 #endif
 
+////////////////////////////////////////////////////////////////////////////////
+
+/**
+ * @brief Growable UTF-8 string builder.
+ *
+ * bstr_builder_t accumulates pieces of text into a single buffer:
+ * - The accumulated bytes are always valid UTF-8
+ * - Invalid input is rejected without modifying the builder
+ * - Not thread-safe, a builder must not be shared between threads without synchronization
+ *
+ * Memory Management:
+ * - Create using bstr_builder_new()
+ * - Consume using bstr_builder_finish(), or discard using bstr_builder_free()
+ */
+typedef struct bstr_builder_t bstr_builder_t;
+
+/// @brief Create an empty string builder.
+/// @return The new builder which must be consumed by \ref bstr_builder_finish()
+///         or released by \ref bstr_builder_free()
+extern bstr_builder_t *bstr_builder_new(void);
+
+/// @brief Discard a string builder and its contents.
+/// @param [in] b The builder to free
+extern void bstr_builder_free(bstr_builder_t *b);
+
+/// @brief Get the number of bytes accumulated in a string builder.
+/// @param [in] b The builder
+/// @return Number of bytes of UTF-8 data
+extern size_t bstr_builder_size(const bstr_builder_t *b);
+
+/// @brief Append a string object to a builder.
+/// @param [in,out] b The builder
+/// @param [in] s The string to append
+extern void bstr_builder_push_str(bstr_builder_t *b, const bstr_t *s);
+
+/// @brief Append a null-terminated UTF-8 C string to a builder.
+/// @param [in,out] b The builder
+/// @param [in] cstr The C string to append
+/// @return false if the C string is not valid UTF-8, in which case nothing is appended
+extern bool bstr_builder_push_cstr(bstr_builder_t *b, const char *cstr);
+
+/// @brief Append a Unicode scalar value to a builder.
+/// @param [in,out] b The builder
+/// @param [in] scalar The Unicode scalar value to append
+/// @return false if the value is a surrogate or above U+10FFFF, in which case nothing is appended
+extern bool bstr_builder_push_char(bstr_builder_t *b, uint32_t scalar);
+
+/// @brief Append the decimal representation of a signed integer to a builder.
+/// @param [in,out] b The builder
+/// @param [in] v The integer to append
+extern void bstr_builder_push_i64(bstr_builder_t *b, int64_t v);
+
+/// @brief Consume a builder and return the accumulated string.
+/// @param [in] b The builder, which is freed and must not be used afterwards
+/// @return New string object which must be released by \ref bstr_release()
+/// @note No copy occurs, the string takes over the builder's buffer
+extern bstr_t bstr_builder_finish(bstr_builder_t *b);
+
+////////////////////////////////////////////////////////////////////////////////
+
+/**
+ * @brief Compiled regular expression.
+ *
+ * bstr_regex_t holds a pattern compiled once for reuse across many strings:
+ * - Uses the syntax of the Rust `regex` crate, matching in linear time
+ * - Immutable after compilation, safe to share between threads
+ * - Requires the `regex` feature
+ *
+ * Memory Management:
+ * - Create using bstr_regex_compile()
+ * - Release using bstr_regex_free()
+ */
+typedef struct bstr_regex_t bstr_regex_t;
+
+/// @brief Test a string against a regular expression compiled for this call only.
+/// @param [in] s The string to test
+/// @param [in] pattern The regular expression
+/// @return 1 if the pattern matches anywhere in s, 0 if it does not, -1 if the pattern is invalid
+/// @note Use bstr_regex_compile() to avoid recompiling a pattern used many times
+/// @note Requires the `regex` feature
+extern int bstr_regex_is_match(const bstr_t *s, const bstr_t *pattern);
+
+/// @brief Compile a regular expression.
+/// @param [in] pattern The regular expression
+/// @return The compiled regex which must be released by \ref bstr_regex_free(),
+///         or NULL if the pattern is invalid
+/// @note Requires the `regex` feature
+extern bstr_regex_t *bstr_regex_compile(const bstr_t *pattern);
+
+/// @brief Release a compiled regular expression.
+/// @param [in] re The regex to free
+/// @note Requires the `regex` feature
+extern void bstr_regex_free(bstr_regex_t *re);
+
+/// @brief Test a string against a compiled regular expression.
+/// @param [in] re The compiled regex
+/// @param [in] s The string to test
+/// @return true if the regex matches anywhere in s, false otherwise or if re is null
+/// @note Requires the `regex` feature
+extern bool bstr_regex_matches(const bstr_regex_t *re, const bstr_t *s);
+
+/// @brief Find the first match of a compiled regular expression.
+/// @param [in] re The compiled regex
+/// @param [in] s The string to search in
+/// @param [out] start Receives the byte offset where the match starts (may be null)
+/// @param [out] end Receives the byte offset just past the end of the match (may be null)
+/// @return true if a match was found, false otherwise or if re is null
+/// @note Both offsets always fall on character boundaries
+/// @note start and end are left unchanged if no match is found
+/// @note Requires the `regex` feature
+extern bool bstr_regex_find(const bstr_regex_t *re, const bstr_t *s, size_t *start, size_t *end);
+
+////////////////////////////////////////////////////////////////////////////////
+
+/**
+ * @brief Incremental BASE64 decoder.
+ *
+ * bstr_base64_decoder_t decodes a BASE64 stream that arrives in arbitrary pieces:
+ * - Each piece emits every complete group decoded so far, buffering the incomplete tail
+ * - ASCII whitespace, such as line breaks, is skipped
+ * - Once invalid input is seen the decoder stays failed and emits nothing more
+ * - Not thread-safe, a decoder must not be shared between threads without synchronization
+ *
+ * Memory Management:
+ * - Create using bstr_base64_decoder_new()
+ * - Consume using bstr_base64_decoder_finish(), or discard using bstr_base64_decoder_free()
+ */
+typedef struct bstr_base64_decoder_t bstr_base64_decoder_t;
+
+/// @brief Create a BASE64 stream decoder.
+/// @return The new decoder which must be consumed by \ref bstr_base64_decoder_finish()
+///         or released by \ref bstr_base64_decoder_free()
+extern bstr_base64_decoder_t *bstr_base64_decoder_new(void);
+
+/// @brief Discard a BASE64 stream decoder without finishing it.
+/// @param [in] decoder The decoder to free
+extern void bstr_base64_decoder_free(bstr_base64_decoder_t *decoder);
+
+/// @brief Feed a piece of BASE64 text to a stream decoder.
+/// @param [in,out] decoder The decoder
+/// @param [in] chunk The next piece of the BASE64 stream
+/// @return New bytes object with the data decoded from every group completed so far,
+///         empty if the decoder has failed
+extern bytes_t bstr_base64_decoder_push(bstr_base64_decoder_t *decoder, const bstr_t *chunk);
+
+/// @brief Finish a BASE64 stream and free the decoder.
+/// @param [in] decoder The decoder, which is freed by this call
+/// @param [out] ok Set to true if the whole stream was valid BASE64, false otherwise (may be null)
+/// @return New bytes object with the data decoded from the final group, if any
+/// @note A final group with its '=' padding omitted is accepted
+/// @note Data after '=' padding fails the stream, as it does for bytes_base64_decode()
+extern bytes_t bstr_base64_decoder_finish(bstr_base64_decoder_t *decoder, bool *ok);
+
+////////////////////////////////////////////////////////////////////////////////
+
+/**
+ * @brief String interning pool.
+ *
+ * bstr_intern_pool_t maps equal strings to one shared buffer:
+ * - Interning a string returns the canonical copy, so equal strings share their storage
+ * - The first occurrence is copied into its own buffer and never pins a larger source buffer
+ * - Interned strings stay alive until both the pool and every returned reference are released
+ * - Not thread-safe, a pool must not be shared between threads without synchronization
+ *
+ * Memory Management:
+ * - Create using bstr_intern_pool_new()
+ * - Release using bstr_intern_pool_free(), strings returned by bstr_intern() remain valid
+ */
+typedef struct bstr_intern_pool_t bstr_intern_pool_t;
+
+/// @brief Create an empty string interning pool.
+/// @return The new pool which must be released by \ref bstr_intern_pool_free()
+extern bstr_intern_pool_t *bstr_intern_pool_new(void);
+
+/// @brief Release a string interning pool.
+/// @param [in] pool The pool to free
+/// @note Strings previously returned by bstr_intern() are unaffected
+extern void bstr_intern_pool_free(bstr_intern_pool_t *pool);
+
+/// @brief Get the canonical copy of a string from an interning pool.
+/// @param [in,out] pool The pool, the string is added if it is not there yet
+/// @param [in] s The string to intern
+/// @return New string object sharing the buffer of the pooled string equal to s
+/// @note If pool is null, a clone of s is returned
+extern bstr_t bstr_intern(bstr_intern_pool_t *pool, const bstr_t *s);
+
 #ifdef __cplusplus
 }
 
@@ -11,12 +11,18 @@ from ctypes import (
     CFUNCTYPE,
     POINTER,
     ArgumentError,
+    c_bool,
     c_char,
     c_char_p,
+    c_double,
+    c_int,
+    c_int64,
     c_size_t,
+    c_ssize_t,
     c_uint8,
     c_uint16,
     c_uint32,
+    c_uint64,
     c_void_p,
     c_wchar,
     c_wchar_p,
@@ -25,7 +31,24 @@ from ctypes import (
 )
 from typing import List, Optional, Union
 
-__all__ = ('Bytes', 'Bstr', 'c_bytes_p', 'c_bstr_p', 'bstr_api')
+__all__ = (
+    'Bytes',
+    'Bstr',
+    'c_bytes_p',
+    'c_bstr_p',
+    'bstr_str_callback',
+    'bstr_char_callback',
+    'bytes_callback',
+    'bstr_malloc_callback',
+    'bstr_free_callback',
+    'bstr_realloc_callback',
+    'bstr_lookup_callback',
+    'bstr_char_predicate',
+    'bytes_predicate',
+    'bstr_token_callback',
+    'bytes_read_callback',
+    'bstr_api',
+)
 
 
 class Bytes(ctypes.Structure):
@@ -262,6 +285,21 @@ class Bstr(ctypes.Structure):
 
     NPOS = c_size_t(-1)
 
+    NFC = 0
+    NFD = 1
+    NFKC = 2
+    NFKD = 3
+
+    CLASS_EMPTY = 0
+    CLASS_ASCII = 1
+    CLASS_UNICODE = 2
+
+    TOKEN_OTHER = 0
+    TOKEN_LETTER = 1
+    TOKEN_DIGIT = 2
+    TOKEN_PUNCT = 3
+    TOKEN_SPACE = 4
+
     _fields_ = [
         ('reserved1', c_void_p),
         ('reserved2', c_void_p),
@@ -468,6 +506,9 @@ class BstrApi:
         prototype = CFUNCTYPE(Bytes, c_size_t)
         self.bytes_zalloc = prototype(('bytes_zalloc', dll))
 
+        prototype = CFUNCTYPE(Bytes, c_uint8, c_size_t)
+        self.bytes_fill = prototype(('bytes_fill', dll))
+
         prototype = CFUNCTYPE(Bytes, c_void_p, c_size_t)
         self.bytes_from_static = prototype(('bytes_from_static', dll))
 
@@ -480,9 +521,21 @@ class BstrApi:
         prototype = CFUNCTYPE(Bytes, c_bytes_p, c_size_t, c_size_t)
         self.bytes_slice = prototype(('bytes_slice', dll))
 
+        prototype = CFUNCTYPE(Bytes, c_bytes_p, c_size_t, c_size_t, POINTER(c_bool))
+        self.bytes_slice_checked = prototype(('bytes_slice_checked', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p, c_size_t)
+        self.bytes_prefix = prototype(('bytes_prefix', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p, c_size_t)
+        self.bytes_suffix = prototype(('bytes_suffix', dll))
+
         prototype = CFUNCTYPE(Bytes, c_bytes_p)
         self.bytes_clone = prototype(('bytes_clone', dll))
 
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, POINTER(c_void_p), POINTER(c_size_t))
+        self.bytes_into_c_buffer = prototype(('bytes_into_c_buffer', dll))
+
         prototype = CFUNCTYPE(c_bytes_p)
         self.bytes_release = prototype(('bytes_release', dll))
 
@@ -492,9 +545,188 @@ class BstrApi:
         prototype = CFUNCTYPE(Bytes, c_bstr_p)
         self.bytes_base64_decode = prototype(('bytes_base64_decode', dll))
 
+        prototype = CFUNCTYPE(c_size_t, c_bstr_p)
+        self.bytes_base64_decoded_len = prototype(('bytes_base64_decoded_len', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bstr_p, c_void_p, c_size_t, POINTER(c_bool))
+        self.bytes_base64_decode_into = prototype(('bytes_base64_decode_into', dll))
+
         prototype = CFUNCTYPE(Bstr, c_bytes_p)
         self.bytes_base64_encode = prototype(('bytes_base64_encode', dll))
 
+        prototype = CFUNCTYPE(c_size_t, c_bytes_p)
+        self.bytes_base64_encoded_len = prototype(('bytes_base64_encoded_len', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bytes_p, c_void_p, c_size_t)
+        self.bytes_base64_encode_into = prototype(('bytes_base64_encode_into', dll))
+
+        prototype = CFUNCTYPE(None, c_bytes_p, c_size_t, bstr_str_callback, c_void_p)
+        self.bytes_base64_encode_chunked = prototype(
+            ('bytes_base64_encode_chunked', dll)
+        )
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p)
+        self.bytes_reverse = prototype(('bytes_reverse', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p, c_bytes_p)
+        self.bytes_xor = prototype(('bytes_xor', dll))
+
+        prototype = CFUNCTYPE(None, c_bytes_p, c_size_t, bytes_callback, c_void_p)
+        self.bytes_for_each_chunk = prototype(('bytes_for_each_chunk', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bytes_p, c_size_t, bytes_callback, c_void_p)
+        self.bytes_for_each_chunk_exact = prototype(('bytes_for_each_chunk_exact', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bytes_p, c_uint8, POINTER(c_bytes_p))
+        self.bytes_split_by_byte = prototype(('bytes_split_by_byte', dll))
+
+        prototype = CFUNCTYPE(None, c_bytes_p, c_size_t)
+        self.bytes_split_free = prototype(('bytes_split_free', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p)
+        self.bytes_trim_ascii = prototype(('bytes_trim_ascii', dll))
+
+        prototype = CFUNCTYPE(
+            c_size_t, c_bytes_p, c_bytes_p, POINTER(POINTER(c_size_t))
+        )
+        self.bytes_find_all = prototype(('bytes_find_all', dll))
+
+        prototype = CFUNCTYPE(None, POINTER(c_size_t), c_size_t)
+        self.bytes_usize_array_free = prototype(('bytes_usize_array_free', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, c_void_p, c_size_t)
+        self.bytes_equals_slice = prototype(('bytes_equals_slice', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p, c_size_t)
+        self.bytes_repeat = prototype(('bytes_repeat', dll))
+
+        if hasattr(dll, 'bytes_random'):
+            prototype = CFUNCTYPE(Bytes, c_size_t)
+            self.bytes_random = prototype(('bytes_random', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bytes_p, c_bytes_p)
+        self.bytes_common_prefix_len = prototype(('bytes_common_prefix_len', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bytes_p, c_size_t)
+        self.bytes_hexdump = prototype(('bytes_hexdump', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, POINTER(c_uint8))
+        self.bytes_split_first = prototype(('bytes_split_first', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, POINTER(c_uint8))
+        self.bytes_split_last = prototype(('bytes_split_last', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, POINTER(c_uint64))
+        self.bytes_read_uvarint = prototype(('bytes_read_uvarint', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_uint64)
+        self.bytes_encode_uvarint = prototype(('bytes_encode_uvarint', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p, c_bytes_p)
+        self.bytes_diff = prototype(('bytes_diff', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p, c_bytes_p, POINTER(c_bool))
+        self.bytes_apply_patch = prototype(('bytes_apply_patch', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, POINTER(c_uint8))
+        self.bytes_read_u8 = prototype(('bytes_read_u8', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, POINTER(c_uint16))
+        self.bytes_read_u16_be = prototype(('bytes_read_u16_be', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, POINTER(c_uint16))
+        self.bytes_read_u16_le = prototype(('bytes_read_u16_le', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, POINTER(c_uint32))
+        self.bytes_read_u32_be = prototype(('bytes_read_u32_be', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, POINTER(c_uint32))
+        self.bytes_read_u32_le = prototype(('bytes_read_u32_le', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, POINTER(c_uint64))
+        self.bytes_read_u64_be = prototype(('bytes_read_u64_be', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, POINTER(c_uint64))
+        self.bytes_read_u64_le = prototype(('bytes_read_u64_le', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, c_size_t, POINTER(c_uint8))
+        self.bytes_peek_u8 = prototype(('bytes_peek_u8', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, c_size_t, POINTER(c_uint16))
+        self.bytes_peek_u16_be = prototype(('bytes_peek_u16_be', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, c_size_t, POINTER(c_uint16))
+        self.bytes_peek_u16_le = prototype(('bytes_peek_u16_le', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, c_size_t, POINTER(c_uint32))
+        self.bytes_peek_u32_be = prototype(('bytes_peek_u32_be', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, c_size_t, POINTER(c_uint32))
+        self.bytes_peek_u32_le = prototype(('bytes_peek_u32_le', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p)
+        self.bytes_is_valid_utf8 = prototype(('bytes_is_valid_utf8', dll))
+
+        prototype = CFUNCTYPE(c_double, c_bytes_p)
+        self.bytes_shannon_entropy = prototype(('bytes_shannon_entropy', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p, c_bytes_p)
+        self.bytes_take_while_in_set = prototype(('bytes_take_while_in_set', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bytes_p, c_size_t)
+        self.bytes_to_c_array = prototype(('bytes_to_c_array', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p, c_size_t)
+        self.bytes_rotate_left = prototype(('bytes_rotate_left', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p)
+        self.bytes_dedup_adjacent = prototype(('bytes_dedup_adjacent', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bytes_p, c_uint8)
+        self.bytes_count_leading = prototype(('bytes_count_leading', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bytes_p, c_uint8)
+        self.bytes_count_trailing = prototype(('bytes_count_trailing', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p, bytes_predicate, c_void_p)
+        self.bytes_retain = prototype(('bytes_retain', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bytes_p, c_uint32)
+        self.bytes_to_bitstring = prototype(('bytes_to_bitstring', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p, c_bytes_p)
+        self.bytes_prepend = prototype(('bytes_prepend', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_void_p, c_size_t, bstr_free_callback, c_void_p)
+        self.bytes_from_owner = prototype(('bytes_from_owner', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, c_bytes_p, c_bytes_p)
+        self.bytes_equals_masked = prototype(('bytes_equals_masked', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p, c_size_t)
+        self.bytes_swap_endian = prototype(('bytes_swap_endian', dll))
+
+        prototype = CFUNCTYPE(c_int, c_bytes_p)
+        self.bytes_max_byte = prototype(('bytes_max_byte', dll))
+
+        prototype = CFUNCTYPE(c_int, c_bytes_p)
+        self.bytes_min_byte = prototype(('bytes_min_byte', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bytes_p, c_size_t, c_uint8)
+        self.bytes_pad_to_align = prototype(('bytes_pad_to_align', dll))
+
+        prototype = CFUNCTYPE(Bytes, bytes_read_callback, c_void_p, c_size_t)
+        self.bytes_from_reader = prototype(('bytes_from_reader', dll))
+
+        prototype = CFUNCTYPE(c_uint16, c_bytes_p)
+        self.bytes_fletcher16 = prototype(('bytes_fletcher16', dll))
+
+        prototype = CFUNCTYPE(c_uint32, c_bytes_p)
+        self.bytes_fletcher32 = prototype(('bytes_fletcher32', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bytes_p, c_bstr_p)
+        self.bytes_append_hex = prototype(('bytes_append_hex', dll))
+
         ####################################################################
 
         prototype = CFUNCTYPE(None, c_bstr_p)
@@ -515,6 +747,15 @@ class BstrApi:
         prototype = CFUNCTYPE(Bstr, c_bytes_p)
         self.bstr_from_bytes = prototype(('bstr_from_bytes', dll))
 
+        prototype = CFUNCTYPE(Bstr, c_bytes_p, POINTER(c_bool))
+        self.bstr_from_bytes_checked = prototype(('bstr_from_bytes_checked', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bytes_p)
+        self.bytes_into_bstr_unchecked = prototype(('bytes_into_bstr_unchecked', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_bstr_p)
+        self.bstr_to_bytes = prototype(('bstr_to_bytes', dll))
+
         prototype = CFUNCTYPE(Bstr, POINTER(c_char), c_size_t)
         self.bstr_from_utf8 = prototype(('bstr_from_utf8', dll))
 
@@ -542,12 +783,18 @@ class BstrApi:
         prototype = CFUNCTYPE(c_char_p, c_bstr_p)
         self.bstr_dup_utf8 = prototype(('bstr_dup_utf8', dll))
 
+        prototype = CFUNCTYPE(POINTER(c_char), c_bstr_p, POINTER(c_size_t))
+        self.bstr_dup_utf8_checked = prototype(('bstr_dup_utf8_checked', dll))
+
         prototype = CFUNCTYPE(POINTER(c_uint16), c_bstr_p)
         self.bstr_dup_utf16 = prototype(('bstr_dup_utf16', dll))
 
         prototype = CFUNCTYPE(POINTER(c_uint32), c_bstr_p)
         self.bstr_dup_utf32 = prototype(('bstr_dup_utf32', dll))
 
+        prototype = CFUNCTYPE(POINTER(c_uint16), c_bstr_p)
+        self.bstr_to_wide_path = prototype(('bstr_to_wide_path', dll))
+
         prototype = CFUNCTYPE(c_wchar_p, c_bstr_p)
         if ctypes.sizeof(c_wchar) == 2:
             self.bstr_dup_wchar = prototype(('bstr_dup_utf16', dll))
@@ -557,6 +804,258 @@ class BstrApi:
         prototype = CFUNCTYPE(None, c_void_p)
         self.bstr_mem_free = prototype(('bstr_mem_free', dll))
 
+        prototype = CFUNCTYPE(
+            None, bstr_malloc_callback, bstr_free_callback, bstr_realloc_callback
+        )
+        self.bstr_set_allocator = prototype(('bstr_set_allocator', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p)
+        self.bstr_json_escape = prototype(('bstr_json_escape', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p)
+        self.bstr_json_escape_ascii = prototype(('bstr_json_escape_ascii', dll))
+
+        prototype = CFUNCTYPE(None, c_bstr_p, bstr_str_callback, c_void_p)
+        self.bstr_for_each_word = prototype(('bstr_for_each_word', dll))
+
+        prototype = CFUNCTYPE(None, c_bstr_p, bstr_char_callback, c_void_p)
+        self.bstr_for_each_char = prototype(('bstr_for_each_char', dll))
+
+        prototype = CFUNCTYPE(None, c_bstr_p, bstr_char_callback, c_void_p)
+        self.bstr_for_each_char_rev = prototype(('bstr_for_each_char_rev', dll))
+
+        if hasattr(dll, 'bstr_normalize'):
+            prototype = CFUNCTYPE(Bstr, c_bstr_p, c_int)
+            self.bstr_normalize = prototype(('bstr_normalize', dll))
+
+        if hasattr(dll, 'bstr_display_width'):
+            prototype = CFUNCTYPE(c_size_t, c_bstr_p)
+            self.bstr_display_width = prototype(('bstr_display_width', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p, c_size_t)
+        self.bstr_truncate_chars = prototype(('bstr_truncate_chars', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p, c_size_t)
+        self.bstr_truncate_chars_ellipsis = prototype(
+            ('bstr_truncate_chars_ellipsis', dll)
+        )
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p)
+        self.bstr_capitalize = prototype(('bstr_capitalize', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p)
+        self.bstr_title_case = prototype(('bstr_title_case', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bstr_p, c_char_p)
+        self.bstr_equals_cstr = prototype(('bstr_equals_cstr', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bstr_p, c_char_p)
+        self.bstr_equals_ignore_case_cstr = prototype(
+            ('bstr_equals_ignore_case_cstr', dll)
+        )
+
+        if hasattr(dll, 'bstr_random_hex'):
+            prototype = CFUNCTYPE(Bstr, c_size_t)
+            self.bstr_random_hex = prototype(('bstr_random_hex', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_char_p, c_int64)
+        self.bstr_format_i64 = prototype(('bstr_format_i64', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_char_p, c_uint64)
+        self.bstr_format_u64 = prototype(('bstr_format_u64', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_char_p, c_double)
+        self.bstr_format_f64 = prototype(('bstr_format_f64', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bstr_p, c_size_t)
+        self.bstr_char_to_byte = prototype(('bstr_char_to_byte', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bstr_p, c_size_t)
+        self.bstr_byte_to_char = prototype(('bstr_byte_to_char', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bstr_p)
+        self.bstr_is_blank = prototype(('bstr_is_blank', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bstr_p, c_bstr_p)
+        self.bstr_count_matches = prototype(('bstr_count_matches', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bstr_p, c_bstr_p)
+        self.bstr_common_prefix_len = prototype(('bstr_common_prefix_len', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bstr_p, c_bstr_p)
+        self.bstr_levenshtein = prototype(('bstr_levenshtein', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bstr_p, c_bstr_p)
+        self.bstr_contains_any = prototype(('bstr_contains_any', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bstr_p, c_bstr_p, c_bstr_p, c_bstr_p)
+        self.bstr_split_once = prototype(('bstr_split_once', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bstr_p, c_bstr_p, c_bstr_p, c_bstr_p)
+        self.bstr_rsplit_once = prototype(('bstr_rsplit_once', dll))
+
+        prototype = CFUNCTYPE(c_int, c_bstr_p)
+        self.bstr_classify = prototype(('bstr_classify', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bstr_p, c_bstr_p)
+        self.bstr_matches_glob = prototype(('bstr_matches_glob', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p, bstr_lookup_callback, c_void_p)
+        self.bstr_expand = prototype(('bstr_expand', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bstr_p, POINTER(c_bstr_p))
+        self.bstr_lines = prototype(('bstr_lines', dll))
+
+        prototype = CFUNCTYPE(None, c_bstr_p, c_size_t)
+        self.bstr_split_free = prototype(('bstr_split_free', dll))
+
+        if hasattr(dll, 'bstr_wrap'):
+            prototype = CFUNCTYPE(Bstr, c_bstr_p, c_size_t)
+            self.bstr_wrap = prototype(('bstr_wrap', dll))
+
+        if hasattr(dll, 'bstr_wrap_hyphenate'):
+            prototype = CFUNCTYPE(Bstr, c_bstr_p, c_size_t)
+            self.bstr_wrap_hyphenate = prototype(('bstr_wrap_hyphenate', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bstr_p, c_size_t, c_size_t, c_bstr_p)
+        self.bstr_eq_range = prototype(('bstr_eq_range', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p, c_bstr_p)
+        self.bstr_indent = prototype(('bstr_indent', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p)
+        self.bstr_dedent = prototype(('bstr_dedent', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bstr_p, c_bstr_p)
+        self.bstr_starts_with_ignore_case = prototype(
+            ('bstr_starts_with_ignore_case', dll)
+        )
+
+        prototype = CFUNCTYPE(c_bool, c_bstr_p, c_bstr_p)
+        self.bstr_ends_with_ignore_case = prototype(('bstr_ends_with_ignore_case', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p)
+        self.bstr_to_snake_case = prototype(('bstr_to_snake_case', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p)
+        self.bstr_to_camel_case = prototype(('bstr_to_camel_case', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p, c_uint32)
+        self.bstr_remove_char = prototype(('bstr_remove_char', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p, bstr_char_predicate, c_void_p)
+        self.bstr_retain = prototype(('bstr_retain', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p, c_size_t, c_uint32)
+        self.bstr_group_digits = prototype(('bstr_group_digits', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bstr_p, POINTER(c_bool))
+        self.bstr_parse_bool = prototype(('bstr_parse_bool', dll))
+
+        prototype = CFUNCTYPE(c_int, c_bstr_p, c_bstr_p, c_size_t)
+        self.bstr_ncompare = prototype(('bstr_ncompare', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p, c_size_t, c_uint32)
+        self.bstr_center = prototype(('bstr_center', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_bstr_p, c_bstr_p)
+        self.bstr_eq_ignore_whitespace = prototype(('bstr_eq_ignore_whitespace', dll))
+
+        prototype = CFUNCTYPE(None, c_bstr_p, bstr_token_callback, c_void_p)
+        self.bstr_for_each_token = prototype(('bstr_for_each_token', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bstr_p, c_uint8, c_bytes_p)
+        self.bstr_to_latin1 = prototype(('bstr_to_latin1', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_void_p, c_size_t)
+        self.bstr_from_latin1 = prototype(('bstr_from_latin1', dll))
+
+        prototype = CFUNCTYPE(None, c_bstr_p, c_bstr_p, bstr_str_callback, c_void_p)
+        self.bstr_for_each_split_inclusive = prototype(
+            ('bstr_for_each_split_inclusive', dll)
+        )
+
+        prototype = CFUNCTYPE(Bstr, c_bstr_p, c_uint32)
+        self.bstr_quote = prototype(('bstr_quote', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_bstr_p, POINTER(POINTER(c_size_t)))
+        self.bstr_char_boundaries = prototype(('bstr_char_boundaries', dll))
+
+        if hasattr(dll, 'bstr_eq_loose'):
+            prototype = CFUNCTYPE(c_bool, c_bstr_p, c_bstr_p)
+            self.bstr_eq_loose = prototype(('bstr_eq_loose', dll))
+
+        ####################################################################
+
+        prototype = CFUNCTYPE(c_void_p)
+        self.bstr_builder_new = prototype(('bstr_builder_new', dll))
+
+        prototype = CFUNCTYPE(None, c_void_p)
+        self.bstr_builder_free = prototype(('bstr_builder_free', dll))
+
+        prototype = CFUNCTYPE(c_size_t, c_void_p)
+        self.bstr_builder_size = prototype(('bstr_builder_size', dll))
+
+        prototype = CFUNCTYPE(None, c_void_p, c_bstr_p)
+        self.bstr_builder_push_str = prototype(('bstr_builder_push_str', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_void_p, c_char_p)
+        self.bstr_builder_push_cstr = prototype(('bstr_builder_push_cstr', dll))
+
+        prototype = CFUNCTYPE(c_bool, c_void_p, c_uint32)
+        self.bstr_builder_push_char = prototype(('bstr_builder_push_char', dll))
+
+        prototype = CFUNCTYPE(None, c_void_p, c_int64)
+        self.bstr_builder_push_i64 = prototype(('bstr_builder_push_i64', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_void_p)
+        self.bstr_builder_finish = prototype(('bstr_builder_finish', dll))
+
+        ####################################################################
+
+        if hasattr(dll, 'bstr_regex_compile'):
+            prototype = CFUNCTYPE(c_int, c_bstr_p, c_bstr_p)
+            self.bstr_regex_is_match = prototype(('bstr_regex_is_match', dll))
+
+            prototype = CFUNCTYPE(c_void_p, c_bstr_p)
+            self.bstr_regex_compile = prototype(('bstr_regex_compile', dll))
+
+            prototype = CFUNCTYPE(None, c_void_p)
+            self.bstr_regex_free = prototype(('bstr_regex_free', dll))
+
+            prototype = CFUNCTYPE(c_bool, c_void_p, c_bstr_p)
+            self.bstr_regex_matches = prototype(('bstr_regex_matches', dll))
+
+            prototype = CFUNCTYPE(
+                c_bool, c_void_p, c_bstr_p, POINTER(c_size_t), POINTER(c_size_t)
+            )
+            self.bstr_regex_find = prototype(('bstr_regex_find', dll))
+
+        ####################################################################
+
+        prototype = CFUNCTYPE(c_void_p)
+        self.bstr_base64_decoder_new = prototype(('bstr_base64_decoder_new', dll))
+
+        prototype = CFUNCTYPE(None, c_void_p)
+        self.bstr_base64_decoder_free = prototype(('bstr_base64_decoder_free', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_void_p, c_bstr_p)
+        self.bstr_base64_decoder_push = prototype(('bstr_base64_decoder_push', dll))
+
+        prototype = CFUNCTYPE(Bytes, c_void_p, POINTER(c_bool))
+        self.bstr_base64_decoder_finish = prototype(('bstr_base64_decoder_finish', dll))
+
+        ####################################################################
+
+        prototype = CFUNCTYPE(c_void_p)
+        self.bstr_intern_pool_new = prototype(('bstr_intern_pool_new', dll))
+
+        prototype = CFUNCTYPE(None, c_void_p)
+        self.bstr_intern_pool_free = prototype(('bstr_intern_pool_free', dll))
+
+        prototype = CFUNCTYPE(Bstr, c_void_p, c_bstr_p)
+        self.bstr_intern = prototype(('bstr_intern', dll))
+
         return dll
 
     def _load_dll(
@@ -642,6 +1141,18 @@ class BstrApi:
 c_bytes_p = POINTER(Bytes)
 c_bstr_p = POINTER(Bstr)
 
+bstr_str_callback = CFUNCTYPE(None, c_bstr_p, c_void_p)
+bstr_char_callback = CFUNCTYPE(None, c_uint32, c_size_t, c_void_p)
+bytes_callback = CFUNCTYPE(None, c_bytes_p, c_void_p)
+bstr_malloc_callback = CFUNCTYPE(c_void_p, c_size_t)
+bstr_free_callback = CFUNCTYPE(None, c_void_p)
+bstr_realloc_callback = CFUNCTYPE(c_void_p, c_void_p, c_size_t)
+bstr_lookup_callback = CFUNCTYPE(c_bool, c_bstr_p, c_void_p, c_bstr_p)
+bstr_char_predicate = CFUNCTYPE(c_bool, c_uint32, c_void_p)
+bytes_predicate = CFUNCTYPE(c_bool, c_uint8, c_void_p)
+bstr_token_callback = CFUNCTYPE(None, c_bstr_p, c_int, c_void_p)
+bytes_read_callback = CFUNCTYPE(c_ssize_t, c_void_p, c_size_t, c_void_p)
+
 api = BstrApi()
 
 
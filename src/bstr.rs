@@ -3,7 +3,7 @@
 #![allow(clippy::missing_safety_doc)]
 
 use ::base64::{engine::general_purpose::STANDARD as base64_standard, Engine as _};
-use ::bytes::Bytes;
+use ::bytes::{Bytes, BytesMut};
 use ::bytestring::ByteString;
 use ::libc;
 use ::std::{
@@ -11,10 +11,93 @@ use ::std::{
     os::raw::*,
     ptr::{self, null_mut},
     slice,
+    sync::atomic::{AtomicPtr, Ordering},
 };
 
 ////////////////////////////////////////////////////////////////////////////////
 
+pub type bstr_malloc_fn = extern "C" fn(size: usize) -> *mut c_void;
+pub type bstr_free_fn = extern "C" fn(ptr: *mut c_void);
+pub type bstr_realloc_fn = extern "C" fn(ptr: *mut c_void, size: usize) -> *mut c_void;
+
+/// A complete set of allocator hooks, published as one value so they never mix.
+struct Allocator {
+    malloc: bstr_malloc_fn,
+    free: bstr_free_fn,
+    realloc: bstr_realloc_fn,
+}
+
+// Null falls back to libc. Installed sets are leaked, a reader may still be using one.
+static ALLOCATOR: AtomicPtr<Allocator> = AtomicPtr::new(null_mut());
+
+unsafe fn mem_malloc(size: usize) -> *mut c_void {
+    match ALLOCATOR.load(Ordering::Acquire).as_ref() {
+        Some(a) => (a.malloc)(size),
+        None => libc::malloc(size),
+    }
+}
+
+unsafe fn mem_free(ptr: *mut c_void) {
+    match ALLOCATOR.load(Ordering::Acquire).as_ref() {
+        Some(a) => (a.free)(ptr),
+        None => libc::free(ptr),
+    }
+}
+
+unsafe fn mem_realloc(ptr: *mut c_void, size: usize) -> *mut c_void {
+    match ALLOCATOR.load(Ordering::Acquire).as_ref() {
+        Some(a) => (a.realloc)(ptr, size),
+        None => libc::realloc(ptr, size),
+    }
+}
+
+/// Borrow a C buffer, treating null as empty.
+unsafe fn raw_slice<'a>(data: *const c_void, len: usize) -> &'a [u8] {
+    if data.is_null() || len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data as _, len)
+    }
+}
+
+/// Borrow a NUL-terminated C string without the terminator, treating null as empty.
+unsafe fn cstr_bytes<'a>(s: *const c_char) -> &'a [u8] {
+    if s.is_null() {
+        &[]
+    } else {
+        std::ffi::CStr::from_ptr(s).to_bytes()
+    }
+}
+
+/// Hand a vector over to C as a raw array, returning its length.
+unsafe fn into_raw_array<T>(v: Vec<T>, out: *mut *mut T) -> usize {
+    let len = v.len();
+    if out.is_null() {
+        drop(v);
+    } else if len == 0 {
+        *out = null_mut();
+    } else {
+        *out = Box::into_raw(v.into_boxed_slice()).cast::<T>();
+    }
+    len
+}
+
+/// Take back an array created by `into_raw_array()`.
+unsafe fn free_raw_array<T>(arr: *mut T, count: usize) {
+    if !arr.is_null() && count > 0 {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(arr, count)));
+    }
+}
+
+/// Move a value into an uninitialized out-parameter, ignoring null.
+unsafe fn write_out<T>(out: *mut T, v: T) {
+    if !out.is_null() {
+        ptr::write(out, v);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
 pub type bytes_t = Bytes;
 
 #[no_mangle]
@@ -46,6 +129,8 @@ pub unsafe extern "C" fn bytes_size(bytes: *const bytes_t) -> usize {
 
 #[no_mangle]
 pub extern "C" fn bytes_new() -> bytes_t {
+    // `Bytes::new()` is a const fn over a static empty slice: it never allocates and
+    // dropping it is a no-op, so empty arrays cost only the by-value copy.
     Bytes::new()
 }
 
@@ -62,6 +147,11 @@ pub extern "C" fn bytes_zalloc(len: usize) -> bytes_t {
     vec![0u8; len].into()
 }
 
+#[no_mangle]
+pub extern "C" fn bytes_fill(byte: u8, len: usize) -> bytes_t {
+    vec![byte; len].into()
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn bytes_from_static(static_data: *const c_void, len: usize) -> bytes_t {
     if static_data.is_null() {
@@ -100,6 +190,36 @@ pub unsafe extern "C" fn bytes_slice(bytes: *const bytes_t, start: usize, stop:
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn bytes_slice_checked(
+    bytes: *const bytes_t,
+    start: usize,
+    stop: usize,
+    ok: *mut bool,
+) -> bytes_t {
+    let len = bytes_size(bytes);
+    let stop = if stop == usize::MAX { len } else { stop };
+    let valid = start <= stop && stop <= len;
+    if !ok.is_null() {
+        *ok = valid;
+    }
+    if valid && !bytes.is_null() {
+        (&*bytes).slice(start..stop)
+    } else {
+        Bytes::new()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_prefix(bytes: *const bytes_t, len: usize) -> bytes_t {
+    bytes_slice(bytes, 0, len)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_suffix(bytes: *const bytes_t, from: usize) -> bytes_t {
+    bytes_slice(bytes, from, usize::MAX)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn bytes_clone(bytes: *const bytes_t) -> bytes_t {
     if bytes.is_null() {
@@ -109,6 +229,23 @@ pub unsafe extern "C" fn bytes_clone(bytes: *const bytes_t) -> bytes_t {
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn bytes_into_c_buffer(
+    bytes: *const bytes_t,
+    out_ptr: *mut *mut c_void,
+    out_len: *mut usize,
+) -> bool {
+    let data: &[u8] = if bytes.is_null() { &[] } else { &*bytes };
+    // Never ask for 0 bytes, malloc(0) may legitimately return NULL.
+    let p = mem_malloc(data.len().max(1)) as *mut u8;
+    if !p.is_null() {
+        ptr::copy_nonoverlapping(data.as_ptr(), p, data.len());
+    }
+    write_out(out_ptr, p as *mut c_void);
+    write_out(out_len, if p.is_null() { 0 } else { data.len() });
+    !p.is_null()
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn bytes_release(s: *mut bytes_t) {
     if !s.is_null() {
@@ -134,6 +271,40 @@ pub unsafe extern "C" fn bytes_base64_decode(str: *const bstr_t) -> bytes_t {
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn bytes_base64_decoded_len(str: *const bstr_t) -> usize {
+    if str.is_null() {
+        return 0;
+    }
+    let s = (&*str).as_bytes();
+    let n = s.len();
+    // The decoder requires padding, so input of any other length cannot decode.
+    if !n.is_multiple_of(4) {
+        return 0;
+    }
+    n / 4 * 3 - s.iter().rev().take(2).take_while(|&&x| x == b'=').count()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_base64_decode_into(
+    str: *const bstr_t,
+    dst: *mut c_void,
+    dst_cap: usize,
+    ok: *mut bool,
+) -> usize {
+    let result = if str.is_null() {
+        Ok(0)
+    } else if dst.is_null() {
+        base64_standard.decode_slice(&*str, &mut [])
+    } else {
+        base64_standard.decode_slice(&*str, slice::from_raw_parts_mut(dst as *mut u8, dst_cap))
+    };
+    if !ok.is_null() {
+        *ok = result.is_ok();
+    }
+    result.unwrap_or(0)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn bytes_base64_encode(bytes: *const bytes_t) -> bstr_t {
     if bytes.is_null() {
@@ -143,205 +314,3218 @@ pub unsafe extern "C" fn bytes_base64_encode(bytes: *const bytes_t) -> bstr_t {
     }
 }
 
-////////////////////////////////////////////////////////////////////////////////
+#[no_mangle]
+pub unsafe extern "C" fn bytes_base64_encoded_len(bytes: *const bytes_t) -> usize {
+    if bytes.is_null() {
+        0
+    } else {
+        ::base64::encoded_len((&*bytes).len(), true).unwrap_or(usize::MAX)
+    }
+}
 
-pub type bstr_t = ByteString;
+#[no_mangle]
+pub unsafe extern "C" fn bytes_base64_encode_into(
+    bytes: *const bytes_t,
+    dst: *mut c_char,
+    dst_cap: usize,
+) -> usize {
+    let len = bytes_base64_encoded_len(bytes);
+    if len > 0 && len <= dst_cap && !dst.is_null() {
+        let dst = slice::from_raw_parts_mut(dst as *mut u8, dst_cap);
+        let _ = base64_standard.encode_slice(&*bytes, dst);
+    }
+    len
+}
 
 #[no_mangle]
-pub unsafe extern "C" fn bstr_init(buf: *mut bstr_t) {
-    if !buf.is_null() {
-        let s = ByteString::new();
-        ptr::copy_nonoverlapping(&s, buf, 1);
-        mem::forget(s);
+pub unsafe extern "C" fn bytes_base64_encode_chunked(
+    bytes: *const bytes_t,
+    chunk_bytes: usize,
+    cb: Option<extern "C" fn(chunk: *const bstr_t, user: *mut c_void)>,
+    user: *mut c_void,
+) {
+    if let (false, Some(cb)) = (bytes.is_null(), cb) {
+        // Only the final chunk may need padding.
+        let chunk_bytes = (chunk_bytes / 3).max(1) * 3;
+        for chunk in (&*bytes).chunks(chunk_bytes) {
+            cb(&base64_standard.encode(chunk).into(), user);
+        }
     }
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bstr_ptr(s: *const bstr_t) -> *const c_char {
-    if s.is_null() {
-        ptr::null()
+pub unsafe extern "C" fn bytes_reverse(bytes: *const bytes_t) -> bytes_t {
+    if bytes.is_null() {
+        Bytes::new()
     } else {
-        (&*s).as_ptr() as _
+        (&*bytes).iter().rev().copied().collect::<Vec<u8>>().into()
     }
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bstr_size(s: *const bstr_t) -> usize {
-    if s.is_null() {
-        0
+pub unsafe extern "C" fn bytes_xor(a: *const bytes_t, b: *const bytes_t) -> bytes_t {
+    if a.is_null() || b.is_null() {
+        Bytes::new()
     } else {
-        (&*s).len()
+        (&*a)
+            .iter()
+            .zip((&*b).iter())
+            .map(|(x, y)| x ^ y)
+            .collect::<Vec<u8>>()
+            .into()
     }
 }
 
 #[no_mangle]
-pub extern "C" fn bstr_new() -> bstr_t {
-    ByteString::new()
+pub unsafe extern "C" fn bytes_for_each_chunk(
+    bytes: *const bytes_t,
+    chunk_size: usize,
+    cb: Option<extern "C" fn(chunk: *const bytes_t, user: *mut c_void)>,
+    user: *mut c_void,
+) {
+    if let (false, Some(cb)) = (bytes.is_null() || chunk_size == 0, cb) {
+        let bytes = &*bytes;
+        for chunk in bytes.chunks(chunk_size) {
+            cb(&bytes.slice_ref(chunk), user);
+        }
+    }
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bstr_from_bytes(bytes: *const bytes_t) -> bstr_t {
+pub unsafe extern "C" fn bytes_for_each_chunk_exact(
+    bytes: *const bytes_t,
+    chunk_size: usize,
+    cb: Option<extern "C" fn(chunk: *const bytes_t, user: *mut c_void)>,
+    user: *mut c_void,
+) -> usize {
     if bytes.is_null() {
-        ByteString::new()
-    } else {
-        let bytes = &*bytes;
-        if let Ok(s) = ByteString::try_from(bytes.clone()) {
-            return s;
+        return 0;
+    }
+    let bytes = &*bytes;
+    if chunk_size == 0 {
+        return bytes.len();
+    }
+    let chunks = bytes.chunks_exact(chunk_size);
+    let rest = chunks.remainder().len();
+    if let Some(cb) = cb {
+        for chunk in chunks {
+            cb(&bytes.slice_ref(chunk), user);
         }
-        String::from_utf8_lossy(bytes).into_owned().into()
     }
+    rest
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bstr_from_static(static_str: *const c_char, len: usize) -> bstr_t {
-    if static_str.is_null() {
-        ByteString::new()
-    } else {
-        let len = if len == usize::MAX {
-            libc::strlen(static_str)
-        } else {
-            len
-        };
-        ByteString::from_static(std::str::from_utf8_unchecked(slice::from_raw_parts(
-            static_str.cast::<u8>(),
-            len,
-        )))
+pub unsafe extern "C" fn bytes_split_by_byte(
+    bytes: *const bytes_t,
+    delim: u8,
+    out: *mut *mut bytes_t,
+) -> usize {
+    if bytes.is_null() {
+        return into_raw_array(Vec::new(), out);
     }
+    let bytes = &*bytes;
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    for i in ::memchr::memchr_iter(delim, bytes) {
+        pieces.push(bytes.slice(start..i));
+        start = i + 1;
+    }
+    pieces.push(bytes.slice(start..));
+    into_raw_array(pieces, out)
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bstr_from_utf8(str: *const c_char, len: usize) -> bstr_t {
-    if str.is_null() {
-        ByteString::new()
-    } else {
-        let len = if len == usize::MAX {
-            libc::strlen(str)
-        } else {
-            len
-        };
-        String::from_utf8_lossy(slice::from_raw_parts(str as _, len as _))
-            .into_owned()
-            .into()
-    }
+pub unsafe extern "C" fn bytes_split_free(arr: *mut bytes_t, count: usize) {
+    free_raw_array(arr, count)
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bstr_from_utf16(str: *const u16, mut len: usize) -> bstr_t {
-    if str.is_null() {
-        ByteString::new()
+pub unsafe extern "C" fn bytes_trim_ascii(bytes: *const bytes_t) -> bytes_t {
+    if bytes.is_null() {
+        Bytes::new()
     } else {
-        if len == usize::MAX {
-            len = 0;
-            while *str.add(len as _) != 0 {
-                len += 1;
-            }
-        }
-        String::from_utf16_lossy(slice::from_raw_parts(str, len as _)).into()
+        let bytes = &*bytes;
+        let is_space = |x: &u8| matches!(x, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c' | b'\x0b');
+        let start = bytes
+            .iter()
+            .position(|x| !is_space(x))
+            .unwrap_or(bytes.len());
+        let stop = bytes
+            .iter()
+            .rposition(|x| !is_space(x))
+            .map_or(start, |i| i + 1);
+        bytes.slice(start..stop)
     }
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bstr_from_utf32(str: *const u32, mut len: usize) -> bstr_t {
-    if str.is_null() {
-        ByteString::new()
+pub unsafe extern "C" fn bytes_find_all(
+    haystack: *const bytes_t,
+    needle: *const bytes_t,
+    out: *mut *mut usize,
+) -> usize {
+    if haystack.is_null() || needle.is_null() || (&*needle).is_empty() {
+        into_raw_array(Vec::new(), out)
     } else {
-        if len == usize::MAX {
-            len = 0;
-            while *str.add(len as _) != 0 {
-                len += 1;
-            }
-        }
-        slice::from_raw_parts(str as *const char, len as _)
-            .iter()
-            .collect::<String>()
-            .into()
+        into_raw_array(
+            ::memchr::memmem::find_iter(&*haystack, &*needle).collect(),
+            out,
+        )
     }
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bstr_clone(s: *const bstr_t) -> bstr_t {
-    if s.is_null() {
-        ByteString::new()
+pub unsafe extern "C" fn bytes_usize_array_free(arr: *mut usize, count: usize) {
+    free_raw_array(arr, count)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_equals_slice(
+    bytes: *const bytes_t,
+    data: *const c_void,
+    len: usize,
+) -> bool {
+    let data = raw_slice(data, len);
+    if bytes.is_null() {
+        data.is_empty()
     } else {
-        (&*s).clone()
+        (&*bytes)[..] == *data
     }
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bstr_release(s: *mut bstr_t) {
-    if !s.is_null() {
-        drop(mem::replace(&mut *s, ByteString::new()));
+pub unsafe extern "C" fn bytes_repeat(bytes: *const bytes_t, n: usize) -> bytes_t {
+    if bytes.is_null() {
+        return Bytes::new();
+    }
+    let bytes = &*bytes;
+    match (n, bytes.len().checked_mul(n)) {
+        (1, _) => bytes.clone(),
+        (_, Some(len)) if len > 0 => bytes.repeat(n).into(),
+        _ => Bytes::new(),
     }
 }
 
+#[cfg(feature = "rand")]
 #[no_mangle]
-pub unsafe extern "C" fn bstr_swap(a: *mut bstr_t, b: *mut bstr_t) {
-    if !a.is_null() && !b.is_null() && !ptr::eq(a, b) {
-        // Swap operation is safe because there's no overlap.
-        #[allow(clippy::swap_ptr_to_ref)]
-        mem::swap(&mut *a, &mut *b);
+pub extern "C" fn bytes_random(len: usize) -> bytes_t {
+    let mut v = vec![0u8; len];
+    match ::getrandom::fill(&mut v) {
+        Ok(()) => v.into(),
+        Err(_) => Bytes::new(),
     }
 }
 
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
 #[no_mangle]
-pub unsafe extern "C" fn bstr_dup_utf8(s: *const bstr_t) -> *mut c_char {
-    if !s.is_null() {
-        let s = &*s;
-        let p = libc::malloc(s.len() + 1) as *mut u8;
-        if !p.is_null() {
-            ptr::copy_nonoverlapping(s.as_ptr(), p, s.len());
-            *p.add(s.len()) = 0;
-            return p as _;
-        }
+pub unsafe extern "C" fn bytes_common_prefix_len(a: *const bytes_t, b: *const bytes_t) -> usize {
+    if a.is_null() || b.is_null() {
+        0
+    } else {
+        common_prefix_len(&*a, &*b)
     }
-    null_mut()
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bstr_dup_utf16(s: *const bstr_t) -> *mut u16 {
-    if !s.is_null() {
-        let s = &*s;
-        let p = libc::malloc((s.len() + 1) * 2) as *mut u16;
-        if !p.is_null() {
-            let mut i = 0;
-            for x in s.encode_utf16() {
-                *p.add(i) = x;
-                i += 1;
+pub unsafe extern "C" fn bytes_hexdump(bytes: *const bytes_t, width: usize) -> bstr_t {
+    use std::fmt::Write as _;
+
+    let data: &[u8] = if bytes.is_null() { &[] } else { &*bytes };
+    let width = if width == 0 { 16 } else { width };
+    let mut out = String::new();
+    for (n, line) in data.chunks(width).enumerate() {
+        let _ = write!(out, "{:08x}  ", n * width);
+        for i in 0..width {
+            if i > 0 && i % 8 == 0 {
+                out.push(' ');
             }
-            *p.add(i) = 0;
-            return if s.len() >= i + 64 / 2 {
-                return libc::realloc(p as _, (i + 1) * 2) as _;
+            match line.get(i) {
+                Some(x) => {
+                    let _ = write!(out, "{:02x} ", x);
+                }
+                None => out.push_str("   "),
+            }
+        }
+        out.push_str(" |");
+        out.extend(line.iter().map(|&x| {
+            if x.is_ascii_graphic() || x == b' ' {
+                x as char
             } else {
-                p
-            };
+                '.'
+            }
+        }));
+        out.push_str("|\n");
+    }
+    if !data.is_empty() {
+        let _ = writeln!(out, "{:08x}", data.len());
+    }
+    out.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_split_first(bytes: *mut bytes_t, out_byte: *mut u8) -> bool {
+    match bytes.as_mut() {
+        Some(b) if !b.is_empty() => {
+            write_out(out_byte, b[0]);
+            *b = b.slice(1..);
+            true
         }
+        _ => false,
     }
-    null_mut()
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bstr_dup_utf32(s: *const bstr_t) -> *mut u32 {
-    if !s.is_null() {
-        let s = &*s;
-        let p = libc::malloc((s.len() + 1) * 4) as *mut u32;
-        if !p.is_null() {
-            let mut i = 0;
-            for x in s.chars() {
-                *p.add(i) = x as _;
-                i += 1;
-            }
-            *p.add(i) = 0;
-            return if s.len() >= i + 64 / 4 {
-                libc::realloc(p as _, (i + 1) * 4) as _
-            } else {
-                p
-            };
+pub unsafe extern "C" fn bytes_split_last(bytes: *mut bytes_t, out_byte: *mut u8) -> bool {
+    match bytes.as_mut() {
+        Some(b) if !b.is_empty() => {
+            let n = b.len() - 1;
+            write_out(out_byte, b[n]);
+            b.truncate(n);
+            true
         }
+        _ => false,
     }
-    null_mut()
+}
+
+/// Decode an unsigned LEB128 varint, returning the value and the number of bytes read.
+fn get_uvarint(b: &[u8]) -> Option<(u64, usize)> {
+    let mut v = 0u64;
+    for (i, &x) in b.iter().enumerate().take(10) {
+        // The 10th byte holds only the top bit of a u64.
+        if i == 9 && x > 1 {
+            return None;
+        }
+        v |= u64::from(x & 0x7f) << (7 * i);
+        if x & 0x80 == 0 {
+            return Some((v, i + 1));
+        }
+    }
+    None
+}
+
+fn put_uvarint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bstr_mem_free(ptr: *mut c_void) {
-    libc::free(ptr)
+pub unsafe extern "C" fn bytes_read_uvarint(bytes: *mut bytes_t, out: *mut u64) -> bool {
+    let Some(b) = bytes.as_mut() else {
+        return false;
+    };
+    match get_uvarint(b) {
+        Some((v, n)) => {
+            write_out(out, v);
+            *b = b.slice(n..);
+            true
+        }
+        None => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn bytes_encode_uvarint(v: u64) -> bytes_t {
+    let mut buf = Vec::with_capacity(10);
+    put_uvarint(&mut buf, v);
+    buf.into()
+}
+
+/// Block size of the copy index built by `bytes_diff`.
+const DIFF_BLOCK: usize = 16;
+const DIFF_HASH_BASE: u64 = 0x100_0000_01b3;
+
+fn diff_hash(block: &[u8]) -> u64 {
+    block.iter().fold(0, |h, &b| {
+        h.wrapping_mul(DIFF_HASH_BASE).wrapping_add(b.into())
+    })
+}
+
+fn diff_insert(out: &mut Vec<u8>, data: &[u8]) {
+    if !data.is_empty() {
+        put_uvarint(out, (data.len() as u64) << 1 | 1);
+        out.extend_from_slice(data);
+    }
+}
+
+fn diff_copy(out: &mut Vec<u8>, offset: usize, len: usize) {
+    put_uvarint(out, (len as u64) << 1);
+    put_uvarint(out, offset as u64);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_diff(base: *const bytes_t, target: *const bytes_t) -> bytes_t {
+    let base: &[u8] = if base.is_null() { &[] } else { &*base };
+    let target: &[u8] = if target.is_null() { &[] } else { &*target };
+
+    // Index the aligned blocks of base, then slide a rolling hash over target to find them.
+    let mut index = ::std::collections::HashMap::new();
+    for (i, block) in base.chunks_exact(DIFF_BLOCK).enumerate() {
+        index.entry(diff_hash(block)).or_insert(i * DIFF_BLOCK);
+    }
+    let top = (1..DIFF_BLOCK).fold(1u64, |p, _| p.wrapping_mul(DIFF_HASH_BASE));
+
+    let mut out = Vec::new();
+    put_uvarint(&mut out, target.len() as u64);
+    let mut literal = 0;
+    let mut i = 0;
+    let mut hash = None;
+    while !index.is_empty() && i + DIFF_BLOCK <= target.len() {
+        let h = hash.unwrap_or_else(|| diff_hash(&target[i..i + DIFF_BLOCK]));
+        if let Some(&off) = index.get(&h) {
+            if base[off..off + DIFF_BLOCK] == target[i..i + DIFF_BLOCK] {
+                // Grow the match in both directions, but not into bytes already emitted.
+                let (mut start, mut from) = (i, off);
+                while start > literal && from > 0 && target[start - 1] == base[from - 1] {
+                    start -= 1;
+                    from -= 1;
+                }
+                let (mut end, mut to) = (i + DIFF_BLOCK, off + DIFF_BLOCK);
+                while end < target.len() && to < base.len() && target[end] == base[to] {
+                    end += 1;
+                    to += 1;
+                }
+                diff_insert(&mut out, &target[literal..start]);
+                diff_copy(&mut out, from, end - start);
+                literal = end;
+                i = end;
+                hash = None;
+                continue;
+            }
+        }
+        hash = target.get(i + DIFF_BLOCK).map(|&b| {
+            let h = h.wrapping_sub(top.wrapping_mul(target[i].into()));
+            h.wrapping_mul(DIFF_HASH_BASE).wrapping_add(b.into())
+        });
+        i += 1;
+    }
+    diff_insert(&mut out, &target[literal..]);
+    out.into()
+}
+
+fn apply_patch(base: &[u8], patch: &[u8]) -> Option<Vec<u8>> {
+    let (len, mut p) = get_uvarint(patch)?;
+    let len = usize::try_from(len).ok()?;
+    // The declared length is untrusted, don't let it drive a huge allocation.
+    let mut out = Vec::with_capacity(len.min(base.len() + patch.len()));
+    while p < patch.len() {
+        let (op, n) = get_uvarint(&patch[p..])?;
+        p += n;
+        let n = usize::try_from(op >> 1).ok()?;
+        if n > len - out.len() {
+            return None;
+        }
+        if op & 1 == 1 {
+            out.extend_from_slice(patch.get(p..p.checked_add(n)?)?);
+            p += n;
+        } else {
+            let (off, k) = get_uvarint(&patch[p..])?;
+            p += k;
+            let off = usize::try_from(off).ok()?;
+            out.extend_from_slice(base.get(off..off.checked_add(n)?)?);
+        }
+    }
+    (out.len() == len).then_some(out)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_apply_patch(
+    base: *const bytes_t,
+    patch: *const bytes_t,
+    ok: *mut bool,
+) -> bytes_t {
+    let base: &[u8] = if base.is_null() { &[] } else { &*base };
+    let patch: &[u8] = if patch.is_null() { &[] } else { &*patch };
+    let r = apply_patch(base, patch);
+    write_out(ok, r.is_some());
+    r.map(Bytes::from).unwrap_or_default()
+}
+
+/// Decode `N` bytes at `offset` with `decode`, returning false if too few remain.
+unsafe fn peek_int<const N: usize, T>(
+    bytes: *const bytes_t,
+    offset: usize,
+    out: *mut T,
+    decode: fn([u8; N]) -> T,
+) -> bool {
+    let Some(b) = bytes.as_ref() else {
+        return false;
+    };
+    match b.get(offset..).and_then(|x| x.first_chunk::<N>()) {
+        Some(&raw) => {
+            write_out(out, decode(raw));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Decode `N` bytes from the front with `decode` and advance past them.
+unsafe fn read_int<const N: usize, T>(
+    bytes: *mut bytes_t,
+    out: *mut T,
+    decode: fn([u8; N]) -> T,
+) -> bool {
+    let ok = peek_int(bytes, 0, out, decode);
+    if ok {
+        let b = &mut *bytes;
+        *b = b.slice(N..);
+    }
+    ok
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_read_u8(bytes: *mut bytes_t, out: *mut u8) -> bool {
+    read_int(bytes, out, u8::from_be_bytes)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_read_u16_be(bytes: *mut bytes_t, out: *mut u16) -> bool {
+    read_int(bytes, out, u16::from_be_bytes)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_read_u16_le(bytes: *mut bytes_t, out: *mut u16) -> bool {
+    read_int(bytes, out, u16::from_le_bytes)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_read_u32_be(bytes: *mut bytes_t, out: *mut u32) -> bool {
+    read_int(bytes, out, u32::from_be_bytes)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_read_u32_le(bytes: *mut bytes_t, out: *mut u32) -> bool {
+    read_int(bytes, out, u32::from_le_bytes)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_read_u64_be(bytes: *mut bytes_t, out: *mut u64) -> bool {
+    read_int(bytes, out, u64::from_be_bytes)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_read_u64_le(bytes: *mut bytes_t, out: *mut u64) -> bool {
+    read_int(bytes, out, u64::from_le_bytes)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_peek_u8(bytes: *const bytes_t, offset: usize, out: *mut u8) -> bool {
+    peek_int(bytes, offset, out, u8::from_be_bytes)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_peek_u16_be(
+    bytes: *const bytes_t,
+    offset: usize,
+    out: *mut u16,
+) -> bool {
+    peek_int(bytes, offset, out, u16::from_be_bytes)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_peek_u16_le(
+    bytes: *const bytes_t,
+    offset: usize,
+    out: *mut u16,
+) -> bool {
+    peek_int(bytes, offset, out, u16::from_le_bytes)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_peek_u32_be(
+    bytes: *const bytes_t,
+    offset: usize,
+    out: *mut u32,
+) -> bool {
+    peek_int(bytes, offset, out, u32::from_be_bytes)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_peek_u32_le(
+    bytes: *const bytes_t,
+    offset: usize,
+    out: *mut u32,
+) -> bool {
+    peek_int(bytes, offset, out, u32::from_le_bytes)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_is_valid_utf8(bytes: *const bytes_t) -> bool {
+    bytes.is_null() || std::str::from_utf8(&*bytes).is_ok()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_shannon_entropy(bytes: *const bytes_t) -> f64 {
+    if bytes.is_null() || (&*bytes).is_empty() {
+        return 0.0;
+    }
+    let bytes = &*bytes;
+    let mut counts = [0usize; 256];
+    for &x in bytes.iter() {
+        counts[x as usize] += 1;
+    }
+    let total = bytes.len() as f64;
+    counts
+        .iter()
+        .filter(|&&n| n > 0)
+        .map(|&n| {
+            let n = n as f64;
+            n / total * (total / n).log2()
+        })
+        .sum()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_take_while_in_set(
+    bytes: *mut bytes_t,
+    set: *const bytes_t,
+) -> bytes_t {
+    let Some(b) = bytes.as_mut() else {
+        return Bytes::new();
+    };
+    let mut table = [false; 256];
+    if !set.is_null() {
+        for &x in (&*set).iter() {
+            table[x as usize] = true;
+        }
+    }
+    let n = b
+        .iter()
+        .position(|&x| !table[x as usize])
+        .unwrap_or(b.len());
+    b.split_to(n)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_to_c_array(bytes: *const bytes_t, per_line: usize) -> bstr_t {
+    use std::fmt::Write as _;
+
+    let data: &[u8] = if bytes.is_null() { &[] } else { &*bytes };
+    let per_line = if per_line == 0 { 12 } else { per_line };
+    let mut out = String::with_capacity(data.len() * 6);
+    for (i, x) in data.iter().enumerate() {
+        if i > 0 {
+            out.push_str(if i % per_line == 0 { ",\n" } else { ", " });
+        }
+        let _ = write!(out, "0x{:02x}", x);
+    }
+    out.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_rotate_left(bytes: *const bytes_t, mid: usize) -> bytes_t {
+    if bytes.is_null() {
+        return Bytes::new();
+    }
+    let bytes = &*bytes;
+    let mid = mid.min(bytes.len());
+    if mid == 0 || mid == bytes.len() {
+        return bytes.clone();
+    }
+    let mut v = Vec::with_capacity(bytes.len());
+    v.extend_from_slice(&bytes[mid..]);
+    v.extend_from_slice(&bytes[..mid]);
+    v.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_dedup_adjacent(bytes: *const bytes_t) -> bytes_t {
+    if bytes.is_null() {
+        Bytes::new()
+    } else {
+        let bytes = &*bytes;
+        let mut v = BytesMut::with_capacity(bytes.len());
+        for &x in bytes.iter() {
+            if v.last() != Some(&x) {
+                v.extend_from_slice(&[x]);
+            }
+        }
+        v.freeze()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_count_leading(bytes: *const bytes_t, byte: u8) -> usize {
+    if bytes.is_null() {
+        0
+    } else {
+        (&*bytes).iter().take_while(|&&x| x == byte).count()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_count_trailing(bytes: *const bytes_t, byte: u8) -> usize {
+    if bytes.is_null() {
+        0
+    } else {
+        (&*bytes).iter().rev().take_while(|&&x| x == byte).count()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_retain(
+    bytes: *const bytes_t,
+    pred: Option<extern "C" fn(byte: u8, user: *mut c_void) -> bool>,
+    user: *mut c_void,
+) -> bytes_t {
+    match (bytes.is_null(), pred) {
+        (true, _) => Bytes::new(),
+        (false, None) => (*bytes).clone(),
+        (false, Some(pred)) => {
+            let v: Vec<u8> = (&*bytes)
+                .iter()
+                .copied()
+                .filter(|&x| pred(x, user))
+                .collect();
+            v.into()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_to_bitstring(bytes: *const bytes_t, sep: u32) -> bstr_t {
+    use std::fmt::Write as _;
+
+    let data: &[u8] = if bytes.is_null() { &[] } else { &*bytes };
+    let sep = char::from_u32(sep).filter(|&c| c != '\0');
+    let mut out = String::with_capacity(data.len() * 9);
+    for (i, x) in data.iter().enumerate() {
+        if let (true, Some(sep)) = (i > 0, sep) {
+            out.push(sep);
+        }
+        let _ = write!(out, "{:08b}", x);
+    }
+    out.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_prepend(head: *const bytes_t, body: *const bytes_t) -> bytes_t {
+    let head: &[u8] = if head.is_null() { &[] } else { &*head };
+    let body: &[u8] = if body.is_null() { &[] } else { &*body };
+    let mut v = Vec::with_capacity(head.len() + body.len());
+    v.extend_from_slice(head);
+    v.extend_from_slice(body);
+    v.into()
+}
+
+/// External memory kept alive by a `Bytes` until its drop callback runs.
+struct ForeignBuffer {
+    data: *const c_void,
+    len: usize,
+    drop_cb: Option<extern "C" fn(ctx: *mut c_void)>,
+    ctx: *mut c_void,
+}
+
+// The C caller guarantees the memory is immutable and the callback thread-safe.
+unsafe impl Send for ForeignBuffer {}
+
+impl AsRef<[u8]> for ForeignBuffer {
+    fn as_ref(&self) -> &[u8] {
+        unsafe { raw_slice(self.data, self.len) }
+    }
+}
+
+impl Drop for ForeignBuffer {
+    fn drop(&mut self) {
+        if let Some(cb) = self.drop_cb {
+            cb(self.ctx);
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_from_owner(
+    data: *const c_void,
+    len: usize,
+    drop_cb: Option<extern "C" fn(ctx: *mut c_void)>,
+    ctx: *mut c_void,
+) -> bytes_t {
+    Bytes::from_owner(ForeignBuffer {
+        data,
+        len,
+        drop_cb,
+        ctx,
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_equals_masked(
+    a: *const bytes_t,
+    b: *const bytes_t,
+    mask: *const bytes_t,
+) -> bool {
+    let a: &[u8] = if a.is_null() { &[] } else { &*a };
+    let b: &[u8] = if b.is_null() { &[] } else { &*b };
+    let mask: &[u8] = if mask.is_null() { &[] } else { &*mask };
+    a.len() == b.len()
+        && a.len() == mask.len()
+        && a.iter()
+            .zip(b)
+            .zip(mask)
+            .all(|((x, y), &m)| m == 0 || x == y)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_swap_endian(bytes: *const bytes_t, element_size: usize) -> bytes_t {
+    if bytes.is_null() {
+        return Bytes::new();
+    }
+    let bytes = &*bytes;
+    if element_size < 2 {
+        return bytes.clone();
+    }
+    let mut v = bytes.to_vec();
+    for element in v.chunks_exact_mut(element_size) {
+        element.reverse();
+    }
+    v.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_max_byte(bytes: *const bytes_t) -> c_int {
+    match bytes.as_ref().and_then(|b| b.iter().max()) {
+        Some(&x) => x.into(),
+        None => -1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_min_byte(bytes: *const bytes_t) -> c_int {
+    match bytes.as_ref().and_then(|b| b.iter().min()) {
+        Some(&x) => x.into(),
+        None => -1,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_pad_to_align(
+    bytes: *const bytes_t,
+    align: usize,
+    pad: u8,
+) -> bytes_t {
+    if bytes.is_null() {
+        return Bytes::new();
+    }
+    let bytes = &*bytes;
+    let rem = if align > 1 { bytes.len() % align } else { 0 };
+    if rem == 0 {
+        return bytes.clone();
+    }
+    let mut v = Vec::new();
+    match bytes.len().checked_add(align - rem) {
+        // Fail instead of aborting on an alignment too large to allocate.
+        Some(len) if v.try_reserve_exact(len).is_ok() => {
+            v.extend_from_slice(bytes);
+            v.resize(len, pad);
+            v.into()
+        }
+        _ => Bytes::new(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_from_reader(
+    read_cb: Option<extern "C" fn(buf: *mut c_void, len: usize, user: *mut c_void) -> isize>,
+    user: *mut c_void,
+    hint: usize,
+) -> bytes_t {
+    let Some(read_cb) = read_cb else {
+        return Bytes::new();
+    };
+    let mut v = Vec::<u8>::with_capacity(hint.max(64));
+    loop {
+        if v.len() == v.capacity() {
+            v.reserve(v.len().max(4096));
+        }
+        let spare = v.capacity() - v.len();
+        let n = read_cb(v.as_mut_ptr().add(v.len()) as _, spare, user);
+        match usize::try_from(n) {
+            Ok(0) => break,
+            Ok(n) if n <= spare => v.set_len(v.len() + n),
+            _ => return Bytes::new(),
+        }
+    }
+    v.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_fletcher16(bytes: *const bytes_t) -> u16 {
+    let data: &[u8] = if bytes.is_null() { &[] } else { &*bytes };
+    let (mut a, mut b) = (0u32, 0u32);
+    for &x in data {
+        a = (a + u32::from(x)) % 255;
+        b = (b + a) % 255;
+    }
+    (b << 8 | a) as u16
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_fletcher32(bytes: *const bytes_t) -> u32 {
+    let data: &[u8] = if bytes.is_null() { &[] } else { &*bytes };
+    let (mut a, mut b) = (0u64, 0u64);
+    for word in data.chunks(2) {
+        let lo = word[0];
+        let hi = word.get(1).copied().unwrap_or(0);
+        a = (a + u64::from(u16::from_le_bytes([lo, hi]))) % 65535;
+        b = (b + a) % 65535;
+    }
+    (b << 16 | a) as u32
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_append_hex(bytes: *mut bytes_t, hex: *const bstr_t) -> bool {
+    let Some(b) = bytes.as_mut() else {
+        return false;
+    };
+    let hex = if hex.is_null() {
+        &[][..]
+    } else {
+        (&*hex).as_bytes()
+    };
+    if hex.len() % 2 != 0 || !hex.iter().all(u8::is_ascii_hexdigit) {
+        return false;
+    } else if hex.is_empty() {
+        return true;
+    }
+    // Grow the buffer in place when no one else shares it, so repeated appends are amortized.
+    let mut v = mem::take(b)
+        .try_into_mut()
+        .unwrap_or_else(|shared| BytesMut::from(&shared[..]));
+    let digit = |x: u8| (x as char).to_digit(16).unwrap_or(0) as u8;
+    v.reserve(hex.len() / 2);
+    v.extend(hex.chunks_exact(2).map(|p| digit(p[0]) << 4 | digit(p[1])));
+    *b = v.freeze();
+    true
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+pub type bstr_t = ByteString;
+
+pub const BSTR_NFC: c_int = 0;
+pub const BSTR_NFD: c_int = 1;
+pub const BSTR_NFKC: c_int = 2;
+pub const BSTR_NFKD: c_int = 3;
+
+pub const BSTR_CLASS_EMPTY: c_int = 0;
+pub const BSTR_CLASS_ASCII: c_int = 1;
+pub const BSTR_CLASS_UNICODE: c_int = 2;
+
+pub const BSTR_TOKEN_OTHER: c_int = 0;
+pub const BSTR_TOKEN_LETTER: c_int = 1;
+pub const BSTR_TOKEN_DIGIT: c_int = 2;
+pub const BSTR_TOKEN_PUNCT: c_int = 3;
+pub const BSTR_TOKEN_SPACE: c_int = 4;
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_init(buf: *mut bstr_t) {
+    if !buf.is_null() {
+        let s = ByteString::new();
+        ptr::copy_nonoverlapping(&s, buf, 1);
+        mem::forget(s);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_ptr(s: *const bstr_t) -> *const c_char {
+    if s.is_null() {
+        ptr::null()
+    } else {
+        (&*s).as_ptr() as _
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_size(s: *const bstr_t) -> usize {
+    if s.is_null() {
+        0
+    } else {
+        (&*s).len()
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn bstr_new() -> bstr_t {
+    ByteString::new()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_from_bytes(bytes: *const bytes_t) -> bstr_t {
+    if bytes.is_null() {
+        ByteString::new()
+    } else {
+        let bytes = &*bytes;
+        if let Ok(s) = ByteString::try_from(bytes.clone()) {
+            return s;
+        }
+        String::from_utf8_lossy(bytes).into_owned().into()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_from_bytes_checked(bytes: *const bytes_t, ok: *mut bool) -> bstr_t {
+    let r = if bytes.is_null() {
+        Ok(ByteString::new())
+    } else {
+        ByteString::try_from((*bytes).clone())
+    };
+    write_out(ok, r.is_ok());
+    r.unwrap_or_default()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bytes_into_bstr_unchecked(bytes: *const bytes_t) -> bstr_t {
+    if bytes.is_null() {
+        ByteString::new()
+    } else {
+        ByteString::from_bytes_unchecked((*bytes).clone())
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_to_bytes(s: *const bstr_t) -> bytes_t {
+    bytes_from_bstr(s)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_from_static(static_str: *const c_char, len: usize) -> bstr_t {
+    if static_str.is_null() {
+        ByteString::new()
+    } else {
+        let len = if len == usize::MAX {
+            libc::strlen(static_str)
+        } else {
+            len
+        };
+        ByteString::from_static(std::str::from_utf8_unchecked(slice::from_raw_parts(
+            static_str.cast::<u8>(),
+            len,
+        )))
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_from_utf8(str: *const c_char, len: usize) -> bstr_t {
+    if str.is_null() {
+        ByteString::new()
+    } else {
+        let len = if len == usize::MAX {
+            libc::strlen(str)
+        } else {
+            len
+        };
+        String::from_utf8_lossy(slice::from_raw_parts(str as _, len as _))
+            .into_owned()
+            .into()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_from_utf16(str: *const u16, mut len: usize) -> bstr_t {
+    if str.is_null() {
+        ByteString::new()
+    } else {
+        if len == usize::MAX {
+            len = 0;
+            while *str.add(len as _) != 0 {
+                len += 1;
+            }
+        }
+        String::from_utf16_lossy(slice::from_raw_parts(str, len as _)).into()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_from_utf32(str: *const u32, mut len: usize) -> bstr_t {
+    if str.is_null() {
+        ByteString::new()
+    } else {
+        if len == usize::MAX {
+            len = 0;
+            while *str.add(len as _) != 0 {
+                len += 1;
+            }
+        }
+        slice::from_raw_parts(str as *const char, len as _)
+            .iter()
+            .collect::<String>()
+            .into()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_clone(s: *const bstr_t) -> bstr_t {
+    if s.is_null() {
+        ByteString::new()
+    } else {
+        (&*s).clone()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_release(s: *mut bstr_t) {
+    if !s.is_null() {
+        drop(mem::replace(&mut *s, ByteString::new()));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_swap(a: *mut bstr_t, b: *mut bstr_t) {
+    if !a.is_null() && !b.is_null() && !ptr::eq(a, b) {
+        // Swap operation is safe because there's no overlap.
+        #[allow(clippy::swap_ptr_to_ref)]
+        mem::swap(&mut *a, &mut *b);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_dup_utf8(s: *const bstr_t) -> *mut c_char {
+    if !s.is_null() {
+        let s = &*s;
+        let p = mem_malloc(s.len() + 1) as *mut u8;
+        if !p.is_null() {
+            ptr::copy_nonoverlapping(s.as_ptr(), p, s.len());
+            *p.add(s.len()) = 0;
+            return p as _;
+        }
+    }
+    null_mut()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_dup_utf8_checked(
+    s: *const bstr_t,
+    out_len: *mut usize,
+) -> *mut c_char {
+    let p = bstr_dup_utf8(s);
+    if !out_len.is_null() {
+        *out_len = if p.is_null() { 0 } else { (&*s).len() };
+    }
+    p
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_dup_utf16(s: *const bstr_t) -> *mut u16 {
+    if !s.is_null() {
+        let s = &*s;
+        let Some(size) = s.len().checked_add(1).and_then(|n| n.checked_mul(2)) else {
+            return null_mut();
+        };
+        let p = mem_malloc(size) as *mut u16;
+        if !p.is_null() {
+            let mut i = 0;
+            for x in s.encode_utf16() {
+                *p.add(i) = x;
+                i += 1;
+            }
+            *p.add(i) = 0;
+            return shrink_dup(p, i + 1, s.len() + 1);
+        }
+    }
+    null_mut()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_dup_utf32(s: *const bstr_t) -> *mut u32 {
+    if !s.is_null() {
+        let s = &*s;
+        let Some(size) = s.len().checked_add(1).and_then(|n| n.checked_mul(4)) else {
+            return null_mut();
+        };
+        let p = mem_malloc(size) as *mut u32;
+        if !p.is_null() {
+            let mut i = 0;
+            for x in s.chars() {
+                *p.add(i) = x as _;
+                i += 1;
+            }
+            *p.add(i) = 0;
+            return shrink_dup(p, i + 1, s.len() + 1);
+        }
+    }
+    null_mut()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_to_wide_path(s: *const bstr_t) -> *mut u16 {
+    // MAX_PATH minus room for an 8.3 file name, the limit for directories.
+    const MAX_PATH: usize = 248;
+
+    if s.is_null() {
+        return null_mut();
+    }
+    let s: &str = &*s;
+    let b = s.as_bytes();
+    let is_sep = |c: u8| c == b'\\' || c == b'/';
+    let is_drive = b.len() >= 3 && b[0].is_ascii_alphabetic() && b[1] == b':' && is_sep(b[2]);
+    let is_unc = b.len() >= 3 && is_sep(b[0]) && is_sep(b[1]) && !matches!(b[2], b'?' | b'.');
+    // The verbatim prefix turns off normalization, so only paths that would not change are
+    // prefixed: no empty, "." or ".." components and none ending in a dot or a space.
+    let root = if is_drive { 3 } else { 2 };
+    let normalized = || {
+        let mut parts = s[root..].split(['\\', '/']).peekable();
+        while let Some(p) = parts.next() {
+            let last = parts.peek().is_none();
+            if (p.is_empty() && !last) || p == "." || p == ".." || p.ends_with(['.', ' ']) {
+                return false;
+            }
+        }
+        true
+    };
+    let mut w: Vec<u16> = Vec::new();
+    // Long absolute paths need the verbatim prefix, which also disables '/' translation.
+    if (is_drive || is_unc) && s.encode_utf16().count() >= MAX_PATH && normalized() {
+        let (prefix, rest) = if is_drive {
+            ("\\\\?\\", s)
+        } else {
+            ("\\\\?\\UNC\\", &s[2..])
+        };
+        w.extend(prefix.encode_utf16());
+        w.extend(
+            rest.encode_utf16()
+                .map(|c| if c == '/' as u16 { '\\' as u16 } else { c }),
+        );
+    } else {
+        w.extend(s.encode_utf16());
+    }
+    w.push(0);
+    let p = mem_malloc(w.len() * 2) as *mut u16;
+    if !p.is_null() {
+        ptr::copy_nonoverlapping(w.as_ptr(), p, w.len());
+    }
+    p
+}
+
+/// Shrink an over-allocated buffer of `cap` elements to `len` if at least 64 bytes are wasted.
+///
+/// The original buffer is kept if `realloc()` fails.
+unsafe fn shrink_dup<T>(p: *mut T, len: usize, cap: usize) -> *mut T {
+    if (cap - len) * mem::size_of::<T>() >= 64 {
+        let q = mem_realloc(p as _, len * mem::size_of::<T>()) as *mut T;
+        if !q.is_null() {
+            return q;
+        }
+    }
+    p
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_mem_free(ptr: *mut c_void) {
+    mem_free(ptr)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_set_allocator(
+    malloc_fn: Option<bstr_malloc_fn>,
+    free_fn: Option<bstr_free_fn>,
+    realloc_fn: Option<bstr_realloc_fn>,
+) {
+    let a = match (malloc_fn, free_fn, realloc_fn) {
+        (Some(malloc), Some(free), Some(realloc)) => Box::into_raw(Box::new(Allocator {
+            malloc,
+            free,
+            realloc,
+        })),
+        _ => null_mut(),
+    };
+    ALLOCATOR.store(a, Ordering::Release);
+}
+
+fn json_escape(s: &str, ascii_only: bool) -> String {
+    use std::fmt::Write as _;
+
+    let mut out = String::with_capacity(s.len() + 2);
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 || (ascii_only && !c.is_ascii()) => {
+                let mut buf = [0u16; 2];
+                for unit in c.encode_utf16(&mut buf) {
+                    let _ = write!(out, "\\u{:04x}", unit);
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+unsafe fn bstr_json_escape_impl(s: *const bstr_t, ascii_only: bool) -> bstr_t {
+    if s.is_null() {
+        return ByteString::new();
+    }
+    let s = &*s;
+    let clean = s
+        .bytes()
+        .all(|x| x >= 0x20 && x != b'"' && x != b'\\' && (!ascii_only || x < 0x80));
+    if clean {
+        s.clone()
+    } else {
+        json_escape(s, ascii_only).into()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_json_escape(s: *const bstr_t) -> bstr_t {
+    bstr_json_escape_impl(s, false)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_json_escape_ascii(s: *const bstr_t) -> bstr_t {
+    bstr_json_escape_impl(s, true)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_for_each_word(
+    s: *const bstr_t,
+    cb: Option<extern "C" fn(word: *const bstr_t, user: *mut c_void)>,
+    user: *mut c_void,
+) {
+    if let (false, Some(cb)) = (s.is_null(), cb) {
+        let s = &*s;
+        for word in s.split_whitespace() {
+            cb(&s.slice_ref(word), user);
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_for_each_char(
+    s: *const bstr_t,
+    cb: Option<extern "C" fn(scalar: u32, byte_offset: usize, user: *mut c_void)>,
+    user: *mut c_void,
+) {
+    if let (false, Some(cb)) = (s.is_null(), cb) {
+        for (i, c) in (&*s).char_indices() {
+            cb(c as _, i, user);
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_for_each_char_rev(
+    s: *const bstr_t,
+    cb: Option<extern "C" fn(scalar: u32, byte_offset: usize, user: *mut c_void)>,
+    user: *mut c_void,
+) {
+    if let (false, Some(cb)) = (s.is_null(), cb) {
+        for (i, c) in (&*s).char_indices().rev() {
+            cb(c as _, i, user);
+        }
+    }
+}
+
+#[cfg(feature = "unicode-normalization")]
+#[no_mangle]
+pub unsafe extern "C" fn bstr_normalize(s: *const bstr_t, form: c_int) -> bstr_t {
+    use ::unicode_normalization::{
+        is_nfc_quick, is_nfd_quick, is_nfkc_quick, is_nfkd_quick, IsNormalized,
+        UnicodeNormalization,
+    };
+
+    if s.is_null() {
+        return ByteString::new();
+    }
+    let s = &*s;
+    let quick = match form {
+        BSTR_NFC => is_nfc_quick(s.chars()),
+        BSTR_NFD => is_nfd_quick(s.chars()),
+        BSTR_NFKC => is_nfkc_quick(s.chars()),
+        BSTR_NFKD => is_nfkd_quick(s.chars()),
+        _ => IsNormalized::Yes,
+    };
+    if quick == IsNormalized::Yes {
+        return s.clone();
+    }
+    match form {
+        BSTR_NFC => s.nfc().collect::<String>().into(),
+        BSTR_NFD => s.nfd().collect::<String>().into(),
+        BSTR_NFKC => s.nfkc().collect::<String>().into(),
+        _ => s.nfkd().collect::<String>().into(),
+    }
+}
+
+#[cfg(feature = "unicode-width")]
+#[no_mangle]
+pub unsafe extern "C" fn bstr_display_width(s: *const bstr_t) -> usize {
+    use ::unicode_width::UnicodeWidthStr;
+
+    if s.is_null() {
+        0
+    } else {
+        (&*s).width()
+    }
+}
+
+fn char_prefix_len(s: &str, max_chars: usize) -> Option<usize> {
+    s.char_indices().nth(max_chars).map(|(i, _)| i)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_truncate_chars(s: *const bstr_t, max_chars: usize) -> bstr_t {
+    if s.is_null() {
+        ByteString::new()
+    } else {
+        let s = &*s;
+        match char_prefix_len(s, max_chars) {
+            Some(end) => s.slice_ref(&s[..end]),
+            None => s.clone(),
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_truncate_chars_ellipsis(
+    s: *const bstr_t,
+    max_chars: usize,
+) -> bstr_t {
+    if s.is_null() {
+        ByteString::new()
+    } else {
+        let s = &*s;
+        match char_prefix_len(s, max_chars) {
+            Some(_) if max_chars == 0 => ByteString::new(),
+            Some(_) => {
+                let end = char_prefix_len(s, max_chars - 1).unwrap_or(s.len());
+                format!("{}\u{2026}", &s[..end]).into()
+            }
+            None => s.clone(),
+        }
+    }
+}
+
+fn capitalize_into(out: &mut String, word: &str) {
+    let mut chars = word.chars();
+    if let Some(c) = chars.next() {
+        out.extend(c.to_uppercase());
+        for c in chars {
+            out.extend(c.to_lowercase());
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_capitalize(s: *const bstr_t) -> bstr_t {
+    if s.is_null() {
+        ByteString::new()
+    } else {
+        let s = &*s;
+        let mut out = String::with_capacity(s.len());
+        capitalize_into(&mut out, s);
+        out.into()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_title_case(s: *const bstr_t) -> bstr_t {
+    if s.is_null() {
+        ByteString::new()
+    } else {
+        let s = &*s;
+        let mut out = String::with_capacity(s.len());
+        let mut rest = &s[..];
+        while !rest.is_empty() {
+            let start = rest
+                .find(|c: char| !c.is_whitespace())
+                .unwrap_or(rest.len());
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            capitalize_into(&mut out, &rest[..end]);
+            rest = &rest[end..];
+        }
+        out.into()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_equals_cstr(s: *const bstr_t, cstr: *const c_char) -> bool {
+    let cstr = cstr_bytes(cstr);
+    if s.is_null() {
+        cstr.is_empty()
+    } else {
+        (&*s).as_bytes()[..] == *cstr
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_equals_ignore_case_cstr(
+    s: *const bstr_t,
+    cstr: *const c_char,
+) -> bool {
+    let cstr = cstr_bytes(cstr);
+    if s.is_null() {
+        cstr.is_empty()
+    } else {
+        (&*s).as_bytes().eq_ignore_ascii_case(cstr)
+    }
+}
+
+#[cfg(feature = "rand")]
+#[no_mangle]
+pub extern "C" fn bstr_random_hex(bytes_len: usize) -> bstr_t {
+    use std::fmt::Write as _;
+
+    let bytes = bytes_random(bytes_len);
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for x in bytes.iter() {
+        let _ = write!(s, "{:02x}", x);
+    }
+    s.into()
+}
+
+/// Largest width or precision accepted by the `bstr_format_*` functions.
+const FORMAT_MAX_WIDTH: usize = 4096;
+
+struct FormatSpec {
+    left: bool,
+    zero: bool,
+    plus: bool,
+    space: bool,
+    alt: bool,
+    width: usize,
+    precision: Option<usize>,
+    conv: u8,
+}
+
+/// Parse a format string with exactly one conversion spec whose conversion is in `convs`,
+/// returning the literal text before and after it with `%%` unescaped.
+unsafe fn parse_format(fmt: *const c_char, convs: &[u8]) -> Option<(String, FormatSpec, String)> {
+    let fmt = std::str::from_utf8(cstr_bytes(fmt)).ok()?;
+    let bytes = fmt.as_bytes();
+    let (mut prefix, mut suffix) = (String::new(), String::new());
+    let mut spec = None;
+    let mut lit = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            i += 1;
+            continue;
+        }
+        let out = if spec.is_none() {
+            &mut prefix
+        } else {
+            &mut suffix
+        };
+        out.push_str(&fmt[lit..i]);
+        i += 1;
+        if bytes.get(i) == Some(&b'%') {
+            out.push('%');
+            i += 1;
+            lit = i;
+            continue;
+        }
+        if spec.is_some() {
+            return None;
+        }
+        let mut s = FormatSpec {
+            left: false,
+            zero: false,
+            plus: false,
+            space: false,
+            alt: false,
+            width: 0,
+            precision: None,
+            conv: 0,
+        };
+        while let Some(&c) = bytes.get(i) {
+            match c {
+                b'-' => s.left = true,
+                b'0' => s.zero = true,
+                b'+' => s.plus = true,
+                b' ' => s.space = true,
+                b'#' => s.alt = true,
+                _ => break,
+            }
+            i += 1;
+        }
+        // An absent number is 0, one over the limit rejects the format string.
+        let digits = |i: &mut usize| {
+            let start = *i;
+            while bytes.get(*i).is_some_and(u8::is_ascii_digit) {
+                *i += 1;
+            }
+            match &fmt[start..*i] {
+                "" => Some(0),
+                n => n.parse::<usize>().ok().filter(|&n| n <= FORMAT_MAX_WIDTH),
+            }
+        };
+        s.width = digits(&mut i)?;
+        if bytes.get(i) == Some(&b'.') {
+            i += 1;
+            s.precision = Some(digits(&mut i)?);
+        }
+        s.conv = *bytes.get(i).filter(|c| convs.contains(c))?;
+        i += 1;
+        lit = i;
+        spec = Some(s);
+    }
+    let spec = spec?;
+    suffix.push_str(&fmt[lit..]);
+    Some((prefix, spec, suffix))
+}
+
+fn format_padded(
+    spec: &FormatSpec,
+    sign: &str,
+    prefix: &str,
+    digits: &str,
+    zero_pad: bool,
+) -> String {
+    let len = sign.len() + prefix.len() + digits.chars().count();
+    let pad = spec.width.saturating_sub(len);
+    let mut out = String::with_capacity(len + pad);
+    if spec.left {
+        out.extend([sign, prefix, digits]);
+        out.extend(std::iter::repeat_n(' ', pad));
+    } else if zero_pad {
+        out.extend([sign, prefix]);
+        out.extend(std::iter::repeat_n('0', pad));
+        out.push_str(digits);
+    } else {
+        out.extend(std::iter::repeat_n(' ', pad));
+        out.extend([sign, prefix, digits]);
+    }
+    out
+}
+
+fn format_integer(spec: &FormatSpec, negative: bool, magnitude: u64) -> String {
+    let mut digits = match spec.conv {
+        b'x' => format!("{:x}", magnitude),
+        b'X' => format!("{:X}", magnitude),
+        b'o' => format!("{:o}", magnitude),
+        b'b' => format!("{:b}", magnitude),
+        _ => magnitude.to_string(),
+    };
+    if let Some(precision) = spec.precision {
+        if precision == 0 && magnitude == 0 {
+            digits.clear();
+        } else if digits.len() < precision {
+            digits.insert_str(0, &"0".repeat(precision - digits.len()));
+        }
+    }
+    // Like printf, '+' and ' ' only apply to signed conversions.
+    let signed = matches!(spec.conv, b'd' | b'i');
+    let sign = match (negative, signed && spec.plus, signed && spec.space) {
+        (true, _, _) => "-",
+        (false, true, _) => "+",
+        (false, false, true) => " ",
+        _ => "",
+    };
+    let prefix = match (spec.alt && magnitude != 0, spec.conv) {
+        (true, b'x') => "0x",
+        (true, b'X') => "0X",
+        (true, b'o') if !digits.starts_with('0') => "0",
+        (true, b'b') => "0b",
+        _ => "",
+    };
+    format_padded(
+        spec,
+        sign,
+        prefix,
+        &digits,
+        spec.zero && spec.precision.is_none(),
+    )
+}
+
+fn format_float(spec: &FormatSpec, v: f64) -> String {
+    let precision = spec.precision.unwrap_or(6);
+    let digits = if !v.is_finite() {
+        let s = if v.is_nan() { "nan" } else { "inf" };
+        if spec.conv == b'E' {
+            s.to_ascii_uppercase()
+        } else {
+            s.to_owned()
+        }
+    } else if spec.conv == b'f' {
+        format!("{:.*}", precision, v.abs())
+    } else {
+        // Rust writes "1.5e2", C writes "1.5e+02".
+        let s = format!("{:.*e}", precision, v.abs());
+        let (mantissa, exp) = s.split_once('e').unwrap_or((&s, "0"));
+        let (exp_sign, exp) = exp.strip_prefix('-').map_or(("+", exp), |x| ("-", x));
+        let e = if spec.conv == b'E' { 'E' } else { 'e' };
+        format!("{}{}{}{:0>2}", mantissa, e, exp_sign, exp)
+    };
+    let sign = match (v.is_sign_negative() && !v.is_nan(), spec.plus, spec.space) {
+        (true, _, _) => "-",
+        (false, true, _) => "+",
+        (false, false, true) => " ",
+        _ => "",
+    };
+    format_padded(spec, sign, "", &digits, spec.zero && v.is_finite())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_format_i64(fmt: *const c_char, v: i64) -> bstr_t {
+    match parse_format(fmt, b"dixXob") {
+        Some((prefix, spec, suffix)) => {
+            let body = match spec.conv {
+                b'd' | b'i' => format_integer(&spec, v < 0, v.unsigned_abs()),
+                _ => format_integer(&spec, false, v as u64),
+            };
+            (prefix + &body + &suffix).into()
+        }
+        None => ByteString::new(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_format_u64(fmt: *const c_char, v: u64) -> bstr_t {
+    match parse_format(fmt, b"uxXob") {
+        Some((prefix, spec, suffix)) => {
+            (prefix + &format_integer(&spec, false, v) + &suffix).into()
+        }
+        None => ByteString::new(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_format_f64(fmt: *const c_char, v: f64) -> bstr_t {
+    match parse_format(fmt, b"feE") {
+        Some((prefix, spec, suffix)) => (prefix + &format_float(&spec, v) + &suffix).into(),
+        None => ByteString::new(),
+    }
+}
+
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    let mut i = index.min(s.len());
+    while !s.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_char_to_byte(s: *const bstr_t, char_index: usize) -> usize {
+    if s.is_null() {
+        0
+    } else {
+        let s = &*s;
+        char_prefix_len(s, char_index).unwrap_or(s.len())
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_byte_to_char(s: *const bstr_t, byte_index: usize) -> usize {
+    if s.is_null() {
+        0
+    } else {
+        let s = &*s;
+        s[..floor_char_boundary(s, byte_index)].chars().count()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_is_blank(s: *const bstr_t) -> bool {
+    s.is_null() || (&*s).trim().is_empty()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_count_matches(s: *const bstr_t, needle: *const bstr_t) -> usize {
+    if s.is_null() || needle.is_null() || (&*needle).is_empty() {
+        0
+    } else {
+        (&*s).matches(&**needle).count()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_common_prefix_len(a: *const bstr_t, b: *const bstr_t) -> usize {
+    if a.is_null() || b.is_null() {
+        0
+    } else {
+        let a = &*a;
+        floor_char_boundary(a, common_prefix_len(a.as_bytes(), (&*b).as_bytes()))
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_levenshtein(a: *const bstr_t, b: *const bstr_t) -> usize {
+    let a = if a.is_null() { "" } else { &**a };
+    let b = if b.is_null() { "" } else { &**b };
+    // Keep the rows as short as the shorter string.
+    let (long, short) = if a.chars().count() >= b.chars().count() {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    let short: Vec<char> = short.chars().collect();
+    let mut prev: Vec<usize> = (0..=short.len()).collect();
+    let mut cur = vec![0; short.len() + 1];
+    for (i, x) in long.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, &y) in short.iter().enumerate() {
+            let cost = usize::from(x != y);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        mem::swap(&mut prev, &mut cur);
+    }
+    prev[short.len()]
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_contains_any(s: *const bstr_t, chars: *const bstr_t) -> bool {
+    if s.is_null() || chars.is_null() {
+        false
+    } else {
+        let chars = &**chars;
+        (&*s).contains(|c: char| chars.contains(c))
+    }
+}
+
+/// Write the zero-copy halves found by `split` into the out-parameters.
+unsafe fn split_halves(
+    s: *const bstr_t,
+    sep: *const bstr_t,
+    left: *mut bstr_t,
+    right: *mut bstr_t,
+    split: for<'a> fn(&'a str, &str) -> Option<(&'a str, &'a str)>,
+) -> bool {
+    let found = if s.is_null() || sep.is_null() {
+        None
+    } else {
+        let s = &*s;
+        split(s, &*sep).map(|(l, r)| (s.slice_ref(l), s.slice_ref(r)))
+    };
+    let ok = found.is_some();
+    let (l, r) = found.unwrap_or_default();
+    write_out(left, l);
+    write_out(right, r);
+    ok
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_split_once(
+    s: *const bstr_t,
+    sep: *const bstr_t,
+    left: *mut bstr_t,
+    right: *mut bstr_t,
+) -> bool {
+    split_halves(s, sep, left, right, |s, sep| s.split_once(sep))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_rsplit_once(
+    s: *const bstr_t,
+    sep: *const bstr_t,
+    left: *mut bstr_t,
+    right: *mut bstr_t,
+) -> bool {
+    split_halves(s, sep, left, right, |s, sep| s.rsplit_once(sep))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_classify(s: *const bstr_t) -> c_int {
+    if s.is_null() || (&*s).is_empty() {
+        BSTR_CLASS_EMPTY
+    } else if (&*s).is_ascii() {
+        BSTR_CLASS_ASCII
+    } else {
+        BSTR_CLASS_UNICODE
+    }
+}
+
+enum GlobToken {
+    Star,
+    Any,
+    Char(char),
+    Class(bool, Vec<(char, char)>),
+}
+
+impl GlobToken {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            GlobToken::Star | GlobToken::Any => true,
+            GlobToken::Char(x) => *x == c,
+            GlobToken::Class(negated, ranges) => {
+                ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi) != *negated
+            }
+        }
+    }
+}
+
+fn parse_glob(pattern: &str) -> Vec<GlobToken> {
+    let p: Vec<char> = pattern.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < p.len() {
+        let token = match p[i] {
+            '*' => GlobToken::Star,
+            '?' => GlobToken::Any,
+            '\\' if i + 1 < p.len() => {
+                i += 1;
+                GlobToken::Char(p[i])
+            }
+            '[' => match parse_glob_class(&p[i + 1..]) {
+                Some((token, used)) => {
+                    i += used;
+                    token
+                }
+                None => GlobToken::Char('['),
+            },
+            c => GlobToken::Char(c),
+        };
+        tokens.push(token);
+        i += 1;
+    }
+    tokens
+}
+
+/// Parse the body of a `[...]` class, returning the token and the number of chars used.
+fn parse_glob_class(p: &[char]) -> Option<(GlobToken, usize)> {
+    let negated = matches!(p.first(), Some('!' | '^'));
+    let mut i = usize::from(negated);
+    let mut ranges = Vec::new();
+    // A ']' right after the opening bracket is a literal.
+    let mut first = true;
+    while i < p.len() {
+        let c = p[i];
+        if c == ']' && !first {
+            return Some((GlobToken::Class(negated, ranges), i + 1));
+        }
+        first = false;
+        if i + 2 < p.len() && p[i + 1] == '-' && p[i + 2] != ']' {
+            ranges.push((c, p[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((c, c));
+            i += 1;
+        }
+    }
+    None
+}
+
+fn glob_match(tokens: &[GlobToken], s: &[char]) -> bool {
+    let (mut t, mut i) = (0, 0);
+    // Position after the last '*' and the input position it is currently matched up to.
+    let mut backtrack = None;
+    while i < s.len() {
+        match tokens.get(t) {
+            Some(GlobToken::Star) => {
+                t += 1;
+                backtrack = Some((t, i));
+            }
+            Some(token) if token.matches(s[i]) => {
+                t += 1;
+                i += 1;
+            }
+            _ => match backtrack {
+                Some((bt, bi)) => {
+                    t = bt;
+                    i = bi + 1;
+                    backtrack = Some((bt, bi + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    tokens[t..].iter().all(|x| matches!(x, GlobToken::Star))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_matches_glob(s: *const bstr_t, pattern: *const bstr_t) -> bool {
+    let s = if s.is_null() { "" } else { &**s };
+    let pattern = if pattern.is_null() { "" } else { &**pattern };
+    let s: Vec<char> = s.chars().collect();
+    glob_match(&parse_glob(pattern), &s)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_expand(
+    s: *const bstr_t,
+    lookup: Option<extern "C" fn(name: *const bstr_t, user: *mut c_void, out: *mut bstr_t) -> bool>,
+    user: *mut c_void,
+) -> bstr_t {
+    if s.is_null() {
+        return ByteString::new();
+    }
+    let s = &*s;
+    let mut out = String::with_capacity(s.len());
+    let mut rest: &str = s;
+    while let Some(i) = rest.find('$') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if let Some(tail) = rest.strip_prefix("$$") {
+            out.push('$');
+            rest = tail;
+        } else if let Some(end) = rest.strip_prefix("${").and_then(|x| x.find('}')) {
+            let name = s.slice_ref(&rest[2..end + 2]);
+            // Whatever the callback leaves in value is released, even if it reports unknown.
+            let mut value = ByteString::new();
+            if lookup.is_some_and(|f| f(&name, user, &mut value)) {
+                out.push_str(&value);
+            } else {
+                out.push_str(&rest[..end + 3]);
+            }
+            rest = &rest[end + 3..];
+        } else {
+            out.push('$');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_lines(s: *const bstr_t, out: *mut *mut bstr_t) -> usize {
+    if s.is_null() {
+        return into_raw_array(Vec::new(), out);
+    }
+    let s = &*s;
+    into_raw_array(s.lines().map(|line| s.slice_ref(line)).collect(), out)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_split_free(arr: *mut bstr_t, count: usize) {
+    free_raw_array(arr, count)
+}
+
+/// Split a word before every character that may start a new grapheme, that is, neither a
+/// zero-width character (combining mark, variation selector, ZWJ) nor a character after a ZWJ.
+#[cfg(feature = "unicode-width")]
+fn wrap_clusters(word: &str) -> impl Iterator<Item = &str> + '_ {
+    use ::unicode_width::UnicodeWidthChar;
+
+    let mut prev = '\0';
+    let mut starts = word.char_indices().filter_map(move |(i, c)| {
+        let joined = c.width() == Some(0) || prev == '\u{200D}';
+        prev = c;
+        (i > 0 && !joined).then_some(i)
+    });
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        if start == word.len() {
+            return None;
+        }
+        let end = starts.next().unwrap_or(word.len());
+        let cluster = &word[start..end];
+        start = end;
+        Some(cluster)
+    })
+}
+
+#[cfg(feature = "unicode-width")]
+fn wrap_line(out: &mut String, line: &str, width: usize, hyphenate: bool, newline: &str) {
+    use ::unicode_width::UnicodeWidthStr;
+
+    // Every width is measured on whole strings, as bstr_display_width() does, because
+    // emoji presentation and ZWJ sequences are wider than the sum of their characters.
+    let mut col = 0;
+    let mut empty = true;
+    let mut words = line.split_whitespace().peekable();
+    // Keep the indentation of the paragraph if its first word still fits after it.
+    let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+    if words
+        .peek()
+        .is_some_and(|w| indent.width() + w.width() <= width)
+    {
+        out.push_str(indent);
+        col = indent.width();
+    }
+    for word in words {
+        let w = word.width();
+        if !empty && col + 1 + w <= width {
+            out.push(' ');
+            col += 1;
+        } else if !empty {
+            out.push_str(newline);
+            col = 0;
+        }
+        empty = false;
+        if w <= width - col {
+            out.push_str(word);
+            col += w;
+            continue;
+        }
+        // Hard-break a word that does not fit on a line of its own, the line is empty here.
+        let (mut start, mut end) = (0, 0);
+        for cluster in wrap_clusters(word) {
+            let next = end + cluster.len();
+            // Reserve a column for the hyphen unless this is the end of the word.
+            let reserve = usize::from(hyphenate && next < word.len());
+            if end > start && word[start..next].width() + reserve > width {
+                out.push_str(&word[start..end]);
+                // A wide character may already have filled the line.
+                if hyphenate && word[start..end].width() < width {
+                    out.push('-');
+                }
+                out.push_str(newline);
+                start = end;
+            }
+            end = next;
+        }
+        out.push_str(&word[start..]);
+        col = word[start..].width();
+    }
+}
+
+#[cfg(feature = "unicode-width")]
+fn wrap(s: &bstr_t, width: usize, hyphenate: bool) -> bstr_t {
+    if width == 0 {
+        return s.clone();
+    }
+    // A line of width 1 has no room for a character and a hyphen.
+    let hyphenate = hyphenate && width > 1;
+    let mut out = String::with_capacity(s.len() + s.len() / width);
+    for line in s.split_inclusive('\n') {
+        // Breaks inside a paragraph use the same terminator as the paragraph itself.
+        let (body, term) = match line.strip_suffix("\r\n") {
+            Some(body) => (body, "\r\n"),
+            None => line
+                .strip_suffix('\n')
+                .map_or((line, ""), |body| (body, "\n")),
+        };
+        wrap_line(
+            &mut out,
+            body,
+            width,
+            hyphenate,
+            if term.is_empty() { "\n" } else { term },
+        );
+        out.push_str(term);
+    }
+    out.into()
+}
+
+#[cfg(feature = "unicode-width")]
+#[no_mangle]
+pub unsafe extern "C" fn bstr_wrap(s: *const bstr_t, width: usize) -> bstr_t {
+    if s.is_null() {
+        ByteString::new()
+    } else {
+        wrap(&*s, width, false)
+    }
+}
+
+#[cfg(feature = "unicode-width")]
+#[no_mangle]
+pub unsafe extern "C" fn bstr_wrap_hyphenate(s: *const bstr_t, width: usize) -> bstr_t {
+    if s.is_null() {
+        ByteString::new()
+    } else {
+        wrap(&*s, width, true)
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_eq_range(
+    a: *const bstr_t,
+    a_start: usize,
+    a_len: usize,
+    b: *const bstr_t,
+) -> bool {
+    let a = if a.is_null() {
+        &[][..]
+    } else {
+        (&*a).as_bytes()
+    };
+    let b = if b.is_null() {
+        &[][..]
+    } else {
+        (&*b).as_bytes()
+    };
+    let start = a_start.min(a.len());
+    let stop = start.saturating_add(a_len).min(a.len());
+    &a[start..stop] == b
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_indent(s: *const bstr_t, prefix: *const bstr_t) -> bstr_t {
+    if s.is_null() {
+        return ByteString::new();
+    }
+    let s = &*s;
+    let prefix = if prefix.is_null() { "" } else { &**prefix };
+    if prefix.is_empty() {
+        return s.clone();
+    }
+    let mut out = String::with_capacity(s.len() + prefix.len() * s.lines().count());
+    for line in s.split_inclusive('\n') {
+        out.push_str(prefix);
+        out.push_str(line);
+    }
+    out.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_dedent(s: *const bstr_t) -> bstr_t {
+    if s.is_null() {
+        return ByteString::new();
+    }
+    let s = &*s;
+    let indent_of = |line: &str| line.len() - line.trim_start_matches([' ', '\t']).len();
+    let mut margin: Option<&str> = None;
+    for line in s.lines().filter(|line| !line.trim().is_empty()) {
+        let indent = &line[..indent_of(line)];
+        margin = Some(match margin {
+            None => indent,
+            Some(m) => &m[..common_prefix_len(m.as_bytes(), indent.as_bytes())],
+        });
+    }
+    let margin = margin.unwrap_or("");
+    let mut out = String::with_capacity(s.len());
+    for line in s.split_inclusive('\n') {
+        let body = line.trim_end_matches(['\r', '\n']);
+        if body.trim().is_empty() {
+            // Whitespace-only lines keep just their line terminator.
+            out.push_str(&line[body.len()..]);
+        } else {
+            out.push_str(&line[margin.len()..]);
+        }
+    }
+    out.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_starts_with_ignore_case(
+    s: *const bstr_t,
+    prefix: *const bstr_t,
+) -> bool {
+    if prefix.is_null() {
+        return true;
+    }
+    let s = if s.is_null() {
+        &[][..]
+    } else {
+        (&*s).as_bytes()
+    };
+    let prefix = (&*prefix).as_bytes();
+    s.len() >= prefix.len() && s[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_ends_with_ignore_case(
+    s: *const bstr_t,
+    suffix: *const bstr_t,
+) -> bool {
+    if suffix.is_null() {
+        return true;
+    }
+    let s = if s.is_null() {
+        &[][..]
+    } else {
+        (&*s).as_bytes()
+    };
+    let suffix = (&*suffix).as_bytes();
+    s.len() >= suffix.len() && s[s.len() - suffix.len()..].eq_ignore_ascii_case(suffix)
+}
+
+/// Split an identifier into words at separators and camelCase humps.
+fn identifier_words(s: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    for part in s.split(|c: char| c == '_' || c == '-' || c.is_whitespace()) {
+        let chars: Vec<(usize, char)> = part.char_indices().collect();
+        let mut start = 0;
+        for k in 1..chars.len() {
+            let (prev, (i, c)) = (chars[k - 1].1, chars[k]);
+            let next_lower = chars.get(k + 1).is_some_and(|&(_, n)| n.is_lowercase());
+            // "fooBar" splits before 'B', "HTTPServer" before 'S'.
+            if c.is_uppercase() && (!prev.is_uppercase() || next_lower) {
+                words.push(&part[start..i]);
+                start = i;
+            }
+        }
+        words.push(&part[start..]);
+    }
+    words.retain(|w| !w.is_empty());
+    words
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_to_snake_case(s: *const bstr_t) -> bstr_t {
+    if s.is_null() {
+        return ByteString::new();
+    }
+    let words: Vec<String> = identifier_words(&*s)
+        .iter()
+        .map(|w| w.to_lowercase())
+        .collect();
+    words.join("_").into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_to_camel_case(s: *const bstr_t) -> bstr_t {
+    if s.is_null() {
+        return ByteString::new();
+    }
+    let mut out = String::new();
+    for (i, word) in identifier_words(&*s).into_iter().enumerate() {
+        let mut chars = word.chars();
+        if let (true, Some(first)) = (i > 0, chars.next()) {
+            out.extend(first.to_uppercase());
+            out.push_str(&chars.as_str().to_lowercase());
+        } else {
+            out.push_str(&word.to_lowercase());
+        }
+    }
+    out.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_remove_char(s: *const bstr_t, ch: u32) -> bstr_t {
+    if s.is_null() {
+        return ByteString::new();
+    }
+    let s = &*s;
+    match char::from_u32(ch) {
+        Some(c) if s.contains(c) => s.replace(c, "").into(),
+        _ => s.clone(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_retain(
+    s: *const bstr_t,
+    pred: Option<extern "C" fn(scalar: u32, user: *mut c_void) -> bool>,
+    user: *mut c_void,
+) -> bstr_t {
+    match (s.is_null(), pred) {
+        (true, _) => ByteString::new(),
+        (false, None) => (*s).clone(),
+        (false, Some(pred)) => {
+            let s: String = (&*s).chars().filter(|&c| pred(c as _, user)).collect();
+            s.into()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_group_digits(
+    s: *const bstr_t,
+    group_size: usize,
+    sep: u32,
+) -> bstr_t {
+    if s.is_null() {
+        return ByteString::new();
+    }
+    let s = &*s;
+    let sign = usize::from(s.starts_with(['-', '+']));
+    let digits = s[sign..].bytes().take_while(u8::is_ascii_digit).count();
+    let Some(sep) = char::from_u32(sep) else {
+        return s.clone();
+    };
+    if group_size == 0 || digits <= group_size {
+        return s.clone();
+    }
+    let mut out = String::with_capacity(s.len() + digits / group_size * sep.len_utf8());
+    out.push_str(&s[..sign]);
+    for (i, c) in s[sign..sign + digits].chars().enumerate() {
+        if i > 0 && (digits - i) % group_size == 0 {
+            out.push(sep);
+        }
+        out.push(c);
+    }
+    out.push_str(&s[sign + digits..]);
+    out.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_parse_bool(s: *const bstr_t, ok: *mut bool) -> bool {
+    const TRUE: [&str; 4] = ["true", "yes", "on", "1"];
+    const FALSE: [&str; 4] = ["false", "no", "off", "0"];
+
+    let s = if s.is_null() { "" } else { &**s };
+    let is_any = |words: &[&str]| words.iter().any(|w| w.eq_ignore_ascii_case(s));
+    let value = is_any(&TRUE);
+    write_out(ok, value || is_any(&FALSE));
+    value
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_ncompare(a: *const bstr_t, b: *const bstr_t, n: usize) -> c_int {
+    let a = if a.is_null() {
+        &[][..]
+    } else {
+        (&*a).as_bytes()
+    };
+    let b = if b.is_null() {
+        &[][..]
+    } else {
+        (&*b).as_bytes()
+    };
+    a[..n.min(a.len())].cmp(&b[..n.min(b.len())]) as c_int
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_center(s: *const bstr_t, total_chars: usize, pad: u32) -> bstr_t {
+    if s.is_null() {
+        return ByteString::new();
+    }
+    let s = &*s;
+    let len = s.chars().count();
+    let Some(pad) = char::from_u32(pad) else {
+        return s.clone();
+    };
+    if len >= total_chars {
+        return s.clone();
+    }
+    let left = (total_chars - len) / 2;
+    let right = total_chars - len - left;
+    let mut out = String::with_capacity(s.len() + (left + right) * pad.len_utf8());
+    out.extend(std::iter::repeat_n(pad, left));
+    out.push_str(s);
+    out.extend(std::iter::repeat_n(pad, right));
+    out.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_eq_ignore_whitespace(a: *const bstr_t, b: *const bstr_t) -> bool {
+    let a = if a.is_null() { "" } else { &**a };
+    let b = if b.is_null() { "" } else { &**b };
+    a.split_whitespace().eq(b.split_whitespace())
+}
+
+fn token_class(c: char) -> c_int {
+    if c.is_alphabetic() {
+        BSTR_TOKEN_LETTER
+    } else if c.is_numeric() {
+        BSTR_TOKEN_DIGIT
+    } else if c.is_whitespace() {
+        BSTR_TOKEN_SPACE
+    } else if c.is_control() {
+        BSTR_TOKEN_OTHER
+    } else {
+        BSTR_TOKEN_PUNCT
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_for_each_token(
+    s: *const bstr_t,
+    cb: Option<extern "C" fn(token: *const bstr_t, class: c_int, user: *mut c_void)>,
+    user: *mut c_void,
+) {
+    let (false, Some(cb)) = (s.is_null(), cb) else {
+        return;
+    };
+    let s = &*s;
+    let mut start = 0;
+    let mut class = BSTR_TOKEN_OTHER;
+    for (i, c) in s.char_indices() {
+        let next = token_class(c);
+        if i > 0 && next != class {
+            cb(&s.slice_ref(&s[start..i]), class, user);
+            start = i;
+        }
+        class = next;
+    }
+    if !s.is_empty() {
+        cb(&s.slice_ref(&s[start..]), class, user);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_to_latin1(
+    s: *const bstr_t,
+    replacement: u8,
+    out: *mut bytes_t,
+) -> usize {
+    let s = if s.is_null() { "" } else { &**s };
+    let mut replaced = 0;
+    let v: Vec<u8> = s
+        .chars()
+        .map(|c| {
+            u8::try_from(c).unwrap_or_else(|_| {
+                replaced += 1;
+                replacement
+            })
+        })
+        .collect();
+    write_out(out, v.into());
+    replaced
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_from_latin1(data: *const c_void, len: usize) -> bstr_t {
+    let s: String = raw_slice(data, len)
+        .iter()
+        .map(|&x| char::from(x))
+        .collect();
+    s.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_for_each_split_inclusive(
+    s: *const bstr_t,
+    sep: *const bstr_t,
+    cb: Option<extern "C" fn(piece: *const bstr_t, user: *mut c_void)>,
+    user: *mut c_void,
+) {
+    let (false, Some(cb)) = (s.is_null(), cb) else {
+        return;
+    };
+    let s = &*s;
+    let sep = if sep.is_null() { "" } else { &**sep };
+    if sep.is_empty() {
+        if !s.is_empty() {
+            cb(s, user);
+        }
+        return;
+    }
+    for piece in s.split_inclusive(sep) {
+        cb(&s.slice_ref(piece), user);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_quote(s: *const bstr_t, quote_char: u32) -> bstr_t {
+    let s = if s.is_null() { "" } else { &**s };
+    let Some(quote) = char::from_u32(quote_char) else {
+        return ByteString::new();
+    };
+    let mut out = String::with_capacity(s.len() + 2 * quote.len_utf8());
+    out.push(quote);
+    for c in s.chars() {
+        if c == quote || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push(quote);
+    out.into()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_char_boundaries(s: *const bstr_t, out: *mut *mut usize) -> usize {
+    let s = if s.is_null() { "" } else { &**s };
+    let mut v: Vec<usize> = s.char_indices().map(|(i, _)| i).collect();
+    v.push(s.len());
+    into_raw_array(v, out)
+}
+
+#[cfg(feature = "unicode-normalization")]
+#[no_mangle]
+pub unsafe extern "C" fn bstr_eq_loose(a: *const bstr_t, b: *const bstr_t) -> bool {
+    use ::unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+    // Uppercasing first applies the full (multi-character) mappings such as 'ß' to "SS" and
+    // merges variants such as final sigma, which lowercasing alone keeps apart. The result is
+    // decomposed again because case mappings may produce composed characters.
+    fn fold(s: &str) -> impl Iterator<Item = char> + '_ {
+        s.nfd()
+            .flat_map(char::to_uppercase)
+            .flat_map(char::to_lowercase)
+            .nfd()
+            .filter(|&c| !is_combining_mark(c))
+    }
+
+    let a = if a.is_null() { "" } else { &**a };
+    let b = if b.is_null() { "" } else { &**b };
+    fold(a).eq(fold(b))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+pub type bstr_builder_t = String;
+
+#[no_mangle]
+pub extern "C" fn bstr_builder_new() -> *mut bstr_builder_t {
+    Box::into_raw(Box::default())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_builder_free(b: *mut bstr_builder_t) {
+    if !b.is_null() {
+        drop(Box::from_raw(b));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_builder_size(b: *const bstr_builder_t) -> usize {
+    if b.is_null() {
+        0
+    } else {
+        (&*b).len()
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_builder_push_str(b: *mut bstr_builder_t, s: *const bstr_t) {
+    if !b.is_null() && !s.is_null() {
+        (&mut *b).push_str(&*s);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_builder_push_cstr(
+    b: *mut bstr_builder_t,
+    cstr: *const c_char,
+) -> bool {
+    if b.is_null() {
+        return false;
+    }
+    match std::str::from_utf8(cstr_bytes(cstr)) {
+        Ok(s) => {
+            (&mut *b).push_str(s);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_builder_push_char(b: *mut bstr_builder_t, scalar: u32) -> bool {
+    match (b.is_null(), char::from_u32(scalar)) {
+        (false, Some(c)) => {
+            (&mut *b).push(c);
+            true
+        }
+        _ => false,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_builder_push_i64(b: *mut bstr_builder_t, v: i64) {
+    use std::fmt::Write as _;
+
+    if !b.is_null() {
+        let _ = write!(&mut *b, "{}", v);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_builder_finish(b: *mut bstr_builder_t) -> bstr_t {
+    if b.is_null() {
+        ByteString::new()
+    } else {
+        (*Box::from_raw(b)).into()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(feature = "regex")]
+pub type bstr_regex_t = ::regex::Regex;
+
+#[cfg(feature = "regex")]
+#[no_mangle]
+pub unsafe extern "C" fn bstr_regex_is_match(s: *const bstr_t, pattern: *const bstr_t) -> c_int {
+    let s = if s.is_null() { "" } else { &**s };
+    let pattern = if pattern.is_null() { "" } else { &**pattern };
+    match ::regex::Regex::new(pattern) {
+        Ok(re) => re.is_match(s).into(),
+        Err(_) => -1,
+    }
+}
+
+#[cfg(feature = "regex")]
+#[no_mangle]
+pub unsafe extern "C" fn bstr_regex_compile(pattern: *const bstr_t) -> *mut bstr_regex_t {
+    let pattern = if pattern.is_null() { "" } else { &**pattern };
+    match ::regex::Regex::new(pattern) {
+        Ok(re) => Box::into_raw(Box::new(re)),
+        Err(_) => null_mut(),
+    }
+}
+
+#[cfg(feature = "regex")]
+#[no_mangle]
+pub unsafe extern "C" fn bstr_regex_free(re: *mut bstr_regex_t) {
+    if !re.is_null() {
+        drop(Box::from_raw(re));
+    }
+}
+
+#[cfg(feature = "regex")]
+#[no_mangle]
+pub unsafe extern "C" fn bstr_regex_matches(re: *const bstr_regex_t, s: *const bstr_t) -> bool {
+    let s = if s.is_null() { "" } else { &**s };
+    re.as_ref().is_some_and(|re| re.is_match(s))
+}
+
+#[cfg(feature = "regex")]
+#[no_mangle]
+pub unsafe extern "C" fn bstr_regex_find(
+    re: *const bstr_regex_t,
+    s: *const bstr_t,
+    start: *mut usize,
+    end: *mut usize,
+) -> bool {
+    let s = if s.is_null() { "" } else { &**s };
+    match re.as_ref().and_then(|re| re.find(s)) {
+        Some(m) => {
+            write_out(start, m.start());
+            write_out(end, m.end());
+            true
+        }
+        None => false,
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+pub struct bstr_base64_decoder_t {
+    /// Incomplete group of fewer than 4 characters.
+    pending: Vec<u8>,
+    padded: bool,
+    failed: bool,
+}
+
+#[no_mangle]
+pub extern "C" fn bstr_base64_decoder_new() -> *mut bstr_base64_decoder_t {
+    Box::into_raw(Box::new(bstr_base64_decoder_t {
+        pending: Vec::with_capacity(4),
+        padded: false,
+        failed: false,
+    }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_base64_decoder_free(decoder: *mut bstr_base64_decoder_t) {
+    if !decoder.is_null() {
+        drop(Box::from_raw(decoder));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_base64_decoder_push(
+    decoder: *mut bstr_base64_decoder_t,
+    chunk: *const bstr_t,
+) -> bytes_t {
+    let Some(d) = decoder.as_mut() else {
+        return Bytes::new();
+    };
+    let chunk = if chunk.is_null() {
+        &[][..]
+    } else {
+        (&*chunk).as_bytes()
+    };
+    let mut input = mem::take(&mut d.pending);
+    input.extend(chunk.iter().filter(|x| !x.is_ascii_whitespace()));
+    if d.failed || (d.padded && !input.is_empty()) {
+        d.failed = true;
+        return Bytes::new();
+    }
+    let complete = input.len() / 4 * 4;
+    // Padding may only end the stream, so it must be in the last complete group.
+    if input[..complete.saturating_sub(4)].contains(&b'=') {
+        d.failed = true;
+        return Bytes::new();
+    }
+    d.padded = input[..complete].ends_with(b"=");
+    d.pending = input.split_off(complete);
+    if d.padded && !d.pending.is_empty() {
+        d.failed = true;
+        return Bytes::new();
+    }
+    match base64_standard.decode(&input) {
+        Ok(v) => v.into(),
+        Err(_) => {
+            d.failed = true;
+            Bytes::new()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_base64_decoder_finish(
+    decoder: *mut bstr_base64_decoder_t,
+    ok: *mut bool,
+) -> bytes_t {
+    if decoder.is_null() {
+        write_out(ok, false);
+        return Bytes::new();
+    }
+    let mut d = Box::from_raw(decoder);
+    // Nothing may follow the padding, just like bytes_base64_decode().
+    d.failed |= d.padded && !d.pending.is_empty();
+    let mut tail = Ok(Vec::new());
+    if !d.pending.is_empty() {
+        // Accept a final group with its padding omitted.
+        d.pending.resize(4, b'=');
+        tail = base64_standard.decode(&d.pending);
+    }
+    write_out(ok, !d.failed && tail.is_ok());
+    match (d.failed, tail) {
+        (false, Ok(v)) => v.into(),
+        _ => Bytes::new(),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+pub struct bstr_intern_pool_t {
+    strings: ::std::collections::HashSet<ByteString>,
+}
+
+#[no_mangle]
+pub extern "C" fn bstr_intern_pool_new() -> *mut bstr_intern_pool_t {
+    Box::into_raw(Box::new(bstr_intern_pool_t {
+        strings: Default::default(),
+    }))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_intern_pool_free(pool: *mut bstr_intern_pool_t) {
+    if !pool.is_null() {
+        drop(Box::from_raw(pool));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bstr_intern(pool: *mut bstr_intern_pool_t, s: *const bstr_t) -> bstr_t {
+    if s.is_null() {
+        return ByteString::new();
+    }
+    let s = &*s;
+    let Some(pool) = pool.as_mut() else {
+        return s.clone();
+    };
+    if let Some(x) = pool.strings.get(&**s) {
+        return x.clone();
+    }
+    // Copy the first occurrence, it may be a view that would pin a whole document buffer.
+    let x = ByteString::from(String::from(&**s));
+    pool.strings.insert(x.clone());
+    x
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+        mem::MaybeUninit,
+    };
+
+    /// Counts the allocations made by the current thread, tests run in parallel.
+    struct CountingAlloc;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAlloc = CountingAlloc;
+
+    fn allocations() -> usize {
+        ALLOCATIONS.with(Cell::get)
+    }
+
+    #[test]
+    fn empty_bytes_never_allocate() {
+        let before = allocations();
+        unsafe {
+            let mut b = bytes_new();
+            bytes_release(&mut b);
+            let mut c = bytes_clone(&b);
+            bytes_release(&mut c);
+
+            let mut raw = MaybeUninit::<bytes_t>::uninit();
+            bytes_init(raw.as_mut_ptr());
+            let mut raw = raw.assume_init();
+            bytes_release(&mut raw);
+            drop((b, c, raw));
+        }
+        assert_eq!(allocations(), before);
+    }
+
+    fn read_uvarint(data: &'static [u8]) -> (Option<u64>, Bytes) {
+        let mut b = Bytes::from_static(data);
+        let mut v = 0;
+        let ok = unsafe { bytes_read_uvarint(&mut b, &mut v) };
+        (ok.then_some(v), b)
+    }
+
+    #[test]
+    fn uvarint_round_trip() {
+        for (v, len) in [(0, 1), (127, 1), (128, 2), (300, 2), (u64::MAX, 10)] {
+            let mut b = bytes_encode_uvarint(v);
+            assert_eq!(b.len(), len, "{v}");
+            b = [&b[..], b"rest"].concat().into();
+            let mut out = 0;
+            assert!(unsafe { bytes_read_uvarint(&mut b, &mut out) });
+            assert_eq!(out, v);
+            assert_eq!(&b[..], b"rest");
+        }
+        assert_eq!(&bytes_encode_uvarint(0)[..], [0x00]);
+        assert_eq!(&bytes_encode_uvarint(127)[..], [0x7f]);
+        assert_eq!(&bytes_encode_uvarint(128)[..], [0x80, 0x01]);
+        assert_eq!(
+            &bytes_encode_uvarint(u64::MAX)[..],
+            [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]
+        );
+    }
+
+    #[test]
+    fn uvarint_rejects_invalid() {
+        const TENTH_BYTE_TOO_BIG: &[u8] =
+            &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02];
+        const ELEVEN_BYTES: &[u8] = &[
+            0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00,
+        ];
+        for data in [
+            TENTH_BYTE_TOO_BIG,
+            ELEVEN_BYTES,
+            &[],
+            &[0x80],
+            &[0xff, 0xff],
+        ] {
+            let (v, rest) = read_uvarint(data);
+            assert_eq!(v, None, "{data:02x?}");
+            assert_eq!(&rest[..], data, "unchanged on failure");
+        }
+        assert!(!unsafe { bytes_read_uvarint(null_mut(), null_mut()) });
+    }
+
+    #[cfg(feature = "unicode-width")]
+    fn wrapped(s: &str, width: usize, hyphenate: bool) -> String {
+        let s = ByteString::from(s);
+        let out = unsafe {
+            if hyphenate {
+                bstr_wrap_hyphenate(&s, width)
+            } else {
+                bstr_wrap(&s, width)
+            }
+        };
+        out.to_string()
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    fn wrap_breaks_at_whitespace() {
+        assert_eq!(
+            wrapped("the quick brown fox", 10, false),
+            "the quick\nbrown fox"
+        );
+        assert_eq!(wrapped("a  b\n\nc", 10, false), "a b\n\nc");
+        assert_eq!(wrapped("abcdefghij", 4, false), "abcd\nefgh\nij");
+        assert_eq!(wrapped("ab abcdefgh", 4, false), "ab\nabcd\nefgh");
+        assert_eq!(wrapped("中文字符", 5, false), "中文\n字符");
+        assert_eq!(wrapped("keep  as is", 0, false), "keep  as is");
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    fn wrap_keeps_graphemes_and_width() {
+        use ::unicode_width::UnicodeWidthStr;
+
+        assert_eq!(
+            wrapped("\u{2764}\u{FE0F}baa", 3, false),
+            "\u{2764}\u{FE0F}b\naa"
+        );
+        assert_eq!(
+            wrapped("e\u{301}e\u{301}e\u{301}", 2, false),
+            "e\u{301}e\u{301}\ne\u{301}"
+        );
+        let zwj = "\u{1F469}\u{200D}\u{1F4BB}";
+        assert!(wrapped(&format!("{zwj}{zwj}{zwj}"), 2, false)
+            .split('\n')
+            .all(|line| line == zwj));
+        for s in [
+            "\u{2764}\u{FE0F}\u{2764}\u{FE0F}\u{2764}\u{FE0F} \u{2764}\u{FE0F}baa",
+            "x\u{1F469}\u{200D}\u{1F4BB}ab\u{1F44D}\u{1F3FD}\u{4E2D}\u{6587}",
+            "a\u{301}\u{302}bc\u{4E2D}d \u{FE0F}e",
+        ] {
+            for width in 2..8 {
+                for hyphenate in [false, true] {
+                    for line in wrapped(s, width, hyphenate).split('\n') {
+                        assert!(line.width() <= width, "{line:?} at {width}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    fn wrap_hyphenates_long_words() {
+        assert_eq!(wrapped("abcdefghij", 5, true), "abcd-\nefgh-\nij");
+        assert_eq!(wrapped("abcde", 5, true), "abcde");
+        assert_eq!(
+            wrapped("the quick abcdefghijkl fox", 6, true),
+            "the\nquick\nabcde-\nfghij-\nkl fox"
+        );
+        // A wide character at the break leaves no room, or only just enough, for the hyphen.
+        assert_eq!(wrapped("漢字漢字漢字", 5, true), "漢字-\n漢字-\n漢字");
+        assert_eq!(wrapped("漢字", 2, true), "漢\n字");
+        assert_eq!(
+            wrapped("\u{2764}\u{FE0F}中abc", 4, true),
+            "\u{2764}\u{FE0F}-\n中a-\nbc"
+        );
+        // Width 1 has no room for a hyphen and falls back to plain hard breaks.
+        assert_eq!(wrapped("abc", 1, true), "a\nb\nc");
+    }
+
+    /// Decode `chunks` with a stream decoder, returning everything it emitted and `ok`.
+    fn base64_stream(chunks: &[&str]) -> (Vec<u8>, bool) {
+        unsafe {
+            let d = bstr_base64_decoder_new();
+            let mut out = Vec::new();
+            for chunk in chunks {
+                out.extend_from_slice(&bstr_base64_decoder_push(d, &ByteString::from(*chunk)));
+            }
+            let mut ok = false;
+            out.extend_from_slice(&bstr_base64_decoder_finish(d, &mut ok));
+            (out, ok)
+        }
+    }
+
+    #[test]
+    fn base64_stream_matches_one_shot() {
+        assert_eq!(
+            base64_stream(&["SGVs", "bG8=\n"]),
+            (b"Hello".to_vec(), true)
+        );
+        assert_eq!(base64_stream(&["S", "GVsbG8"]), (b"Hello".to_vec(), true));
+        assert_eq!(base64_stream(&[]), (Vec::new(), true));
+        let one_shot = unsafe { bytes_base64_decode(&ByteString::from("QQ==QQ")) };
+        assert!(one_shot.is_empty());
+        assert_eq!(base64_stream(&["QQ==QQ"]), (Vec::new(), false));
+        assert!(!base64_stream(&["QQ==", "QQ"]).1);
+        assert!(!base64_stream(&["QQ=", "=QQ"]).1);
+        assert!(!base64_stream(&["QQ==", "QQ=="]).1);
+    }
+
+    #[test]
+    fn append_hex() {
+        let hex = |s: &str| ByteString::from(s);
+        let mut b = Bytes::from_static(b"\x01");
+        unsafe {
+            assert!(bytes_append_hex(&mut b, &hex("02aB")));
+            assert!(bytes_append_hex(&mut b, &hex("")));
+            assert!(bytes_append_hex(&mut b, &hex("FF")));
+            assert_eq!(&b[..], b"\x01\x02\xab\xff");
+            // Appending to a shared array leaves the other owner untouched.
+            let shared = b.clone();
+            assert!(bytes_append_hex(&mut b, &hex("00")));
+            assert_eq!(&shared[..], b"\x01\x02\xab\xff");
+            assert_eq!(&b[..], b"\x01\x02\xab\xff\x00");
+            for bad in ["abc", "0g", "zz00", "\u{e9}\u{e9}", "0 "] {
+                assert!(!bytes_append_hex(&mut b, &hex(bad)), "{bad:?}");
+                assert_eq!(&b[..], b"\x01\x02\xab\xff\x00", "unchanged on failure");
+            }
+            assert!(!bytes_append_hex(null_mut(), &hex("00")));
+        }
+    }
+
+    fn diff(base: &[u8], target: &[u8]) -> Bytes {
+        unsafe {
+            bytes_diff(
+                &Bytes::copy_from_slice(base),
+                &Bytes::copy_from_slice(target),
+            )
+        }
+    }
+
+    fn patch(base: &[u8], patch: &[u8]) -> Option<Bytes> {
+        let mut ok = false;
+        let out = unsafe {
+            bytes_apply_patch(
+                &Bytes::copy_from_slice(base),
+                &Bytes::copy_from_slice(patch),
+                &mut ok,
+            )
+        };
+        assert!(ok || out.is_empty());
+        ok.then_some(out)
+    }
+
+    #[test]
+    fn diff_round_trip() {
+        let text: Vec<u8> = (0..4000u32).map(|i| (i * 7919 % 251) as u8).collect();
+        let mut inserted = text.clone();
+        inserted.splice(1000..1000, *b"a few new bytes");
+        let mut deleted = text.clone();
+        deleted.drain(2000..2100);
+        let moved = [&text[3000..], &text[..3000]].concat();
+        let unrelated: Vec<u8> = (0..3000u32).map(|i| (i * 31 % 256) as u8).collect();
+        let cases: [(&[u8], &[u8]); 8] = [
+            (b"", b""),
+            (b"", b"abc"),
+            (&text, b""),
+            (&text, &text),
+            (&text, &unrelated),
+            (&text, &inserted),
+            (&text, &deleted),
+            (&text, &moved),
+        ];
+        for (base, target) in cases {
+            let p = diff(base, target);
+            assert_eq!(patch(base, &p).as_deref(), Some(target));
+        }
+        // Shared blocks are copied instead of resent.
+        for target in [&text, &inserted, &deleted, &moved] {
+            assert!(diff(&text, target).len() < 64);
+        }
+    }
+
+    #[test]
+    fn apply_patch_rejects_malformed() {
+        let base = b"0123456789";
+        assert_eq!(patch(base, b"\x03\x06\x00").as_deref(), Some(&b"012"[..]));
+        assert_eq!(patch(base, b"\x02\x05ab").as_deref(), Some(&b"ab"[..]));
+        for bad in [
+            &b""[..],                // no length
+            b"\x03\x06\x08",         // copy past the end of base
+            b"\x03\x06\xff\xff\x7f", // copy from far outside base
+            b"\x03\x06",             // copy without offset
+            b"\x03\x07ab",           // truncated insert
+            b"\x03\x06\x80",         // truncated varint
+            b"\x04\x06\x00",         // shorter than declared
+            b"\x02\x06\x00",         // longer than declared
+            b"\x80",                 // truncated length
+        ] {
+            assert_eq!(patch(base, bad), None, "{bad:02x?}");
+        }
+    }
+
+    #[test]
+    fn xor() {
+        let a = Bytes::from_static(b"\x0f\xf0\xaa\x55");
+        let b = Bytes::from_static(b"\xff\xff\x0f");
+        unsafe {
+            assert_eq!(&bytes_xor(&a, &a)[..], [0, 0, 0, 0]);
+            assert_eq!(&bytes_xor(&a, &b)[..], [0xf0, 0x0f, 0xa5]);
+            assert_eq!(&bytes_xor(&b, &a)[..], [0xf0, 0x0f, 0xa5]);
+            assert_eq!(&bytes_xor(&bytes_xor(&a, &b), &b)[..], &a[..3]);
+            assert!(bytes_xor(&a, &Bytes::new()).is_empty());
+            assert!(bytes_xor(&a, ptr::null()).is_empty());
+            assert!(bytes_xor(ptr::null(), &b).is_empty());
+        }
+    }
+
+    #[test]
+    fn format_sign_flags() {
+        let fmt_i = |f: &str, v| unsafe {
+            bstr_format_i64(std::ffi::CString::new(f).unwrap().as_ptr(), v).to_string()
+        };
+        let fmt_u = |f: &str, v| unsafe {
+            bstr_format_u64(std::ffi::CString::new(f).unwrap().as_ptr(), v).to_string()
+        };
+        assert_eq!(fmt_i("%+d", 42), "+42");
+        assert_eq!(fmt_i("% i", 42), " 42");
+        assert_eq!(fmt_i("%+d", -42), "-42");
+        assert_eq!(fmt_i("%+x", -42), "ffffffffffffffd6");
+        assert_eq!(fmt_i("% o", 8), "10");
+        assert_eq!(fmt_u("%+u", 0), "0");
+        assert_eq!(fmt_u("% u", 7), "7");
+        assert_eq!(fmt_u("%+#X", 255), "0XFF");
+        assert_eq!(fmt_u("%+05b", 5), "00101");
+    }
+
+    #[test]
+    fn base64_decoded_len_matches_decoder() {
+        let mut buf = [0u8; 16];
+        for s in [
+            "", "QQ", "QQ=", "QQ==", "QUI=", "QUJD", "QUJDRA", "QUJDRA==", "Q", "QUJDR",
+        ] {
+            let s = ByteString::from(s);
+            let mut ok = false;
+            let len = unsafe { bytes_base64_decoded_len(&s) };
+            let n = unsafe { bytes_base64_decode_into(&s, buf.as_mut_ptr().cast(), len, &mut ok) };
+            // Valid input decodes to exactly the computed length, invalid input to nothing.
+            assert_eq!(n, len, "{s:?}");
+            assert_eq!(ok, len > 0 || s.is_empty(), "{s:?}");
+        }
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    fn wrap_keeps_indentation_and_terminators() {
+        assert_eq!(
+            wrapped("  indented line here\r\nnext", 8, false),
+            "indented\r\nline\r\nhere\r\nnext"
+        );
+        assert_eq!(
+            wrapped("  indented line here\r\nnext", 10, false),
+            "  indented\r\nline here\r\nnext"
+        );
+        assert_eq!(wrapped("    a b c d\n", 7, false), "    a b\nc d\n");
+        assert_eq!(wrapped("\ta  b\n   \nc", 8, false), "\ta b\n\nc");
+        assert_eq!(wrapped("abcdef\r\n", 4, true), "abc-\r\ndef\r\n");
+    }
+
+    #[test]
+    fn dedup_adjacent() {
+        let dedup =
+            |data: &'static [u8]| unsafe { bytes_dedup_adjacent(&Bytes::from_static(data)) };
+        assert_eq!(&dedup(b"aaabccddda")[..], b"abcda");
+        assert_eq!(&dedup(b"abc")[..], b"abc");
+        assert_eq!(&dedup(b"\0\0\0")[..], b"\0");
+        assert!(dedup(b"").is_empty());
+        assert!(unsafe { bytes_dedup_adjacent(ptr::null()) }.is_empty());
+    }
+
+    #[test]
+    fn prepend_allocates_once() {
+        let head = Bytes::from_static(b"head:");
+        let body = Bytes::from_static(b"body");
+        let before = allocations();
+        let out = unsafe { bytes_prepend(&head, &body) };
+        assert_eq!(allocations() - before, 1);
+        assert_eq!(&out[..], b"head:body");
+        unsafe {
+            assert_eq!(&bytes_prepend(&head, ptr::null())[..], b"head:");
+            assert_eq!(&bytes_prepend(ptr::null(), &body)[..], b"body");
+            assert_eq!(&bytes_prepend(&Bytes::new(), &body)[..], b"body");
+            assert_eq!(&bytes_prepend(&head, &Bytes::new())[..], b"head:");
+            let before = allocations();
+            assert!(bytes_prepend(ptr::null(), &Bytes::new()).is_empty());
+            assert_eq!(allocations(), before);
+        }
+    }
+
+    #[test]
+    fn pad_to_align() {
+        let pad = |data: &'static [u8], align| unsafe {
+            bytes_pad_to_align(&Bytes::from_static(data), align, 0xee)
+        };
+        assert_eq!(&pad(b"abc", 0)[..], b"abc");
+        assert_eq!(&pad(b"abc", 1)[..], b"abc");
+        assert_eq!(&pad(b"abcd", 4)[..], b"abcd");
+        assert_eq!(&pad(b"abcde", 4)[..], b"abcde\xee\xee\xee");
+        assert_eq!(&pad(b"abc", 8)[..], b"abc\xee\xee\xee\xee\xee");
+        assert_eq!(&pad(b"abcdefgh", 8)[..], b"abcdefgh");
+        assert!(pad(b"", 8).is_empty());
+        assert!(unsafe { bytes_pad_to_align(ptr::null(), 8, 0) }.is_empty());
+        assert!(pad(b"abc", usize::MAX).is_empty());
+        assert!(pad(b"abc", usize::MAX - 1).is_empty());
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn eq_loose_folds_case_and_marks() {
+        let eq =
+            |a: &str, b: &str| unsafe { bstr_eq_loose(&ByteString::from(a), &ByteString::from(b)) };
+        assert!(eq("Café", "CAFE"));
+        assert!(eq("straße", "STRASSE"));
+        assert!(eq("Straße", "strasse"));
+        assert!(eq("ﬁle", "FILE"));
+        assert!(eq("ΣΊΣΥΦΟΣ", "σίσυφος"));
+        assert!(eq("µ", "μ"));
+        assert!(eq("İstanbul", "istanbul"));
+        assert!(eq("", ""));
+        assert!(!eq("cafe", "cafes"));
+        assert!(!eq("strase", "straße"));
+    }
 }
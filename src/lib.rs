@@ -1,3 +1,7 @@
 mod bstr;
+#[cfg(feature = "serde")]
+mod serde;
 
 pub use crate::bstr::*;
+#[cfg(feature = "serde")]
+pub use crate::serde::*;
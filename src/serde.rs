@@ -0,0 +1,163 @@
+use ::base64::{engine::general_purpose::STANDARD as base64_standard, Engine as _};
+use ::bytes::Bytes;
+use ::bytestring::ByteString;
+use ::serde::{
+    de::{self, Deserialize, Deserializer, SeqAccess, Visitor},
+    ser::{Serialize, Serializer},
+};
+use ::std::{fmt, ops::Deref};
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A serde-enabled wrapper of [`Bytes`].
+///
+/// Serialized as a BASE64 string for human-readable formats and as a byte array otherwise.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SerdeBytes(pub Bytes);
+
+impl SerdeBytes {
+    pub fn into_inner(self) -> Bytes {
+        self.0
+    }
+}
+
+impl Deref for SerdeBytes {
+    type Target = Bytes;
+
+    fn deref(&self) -> &Bytes {
+        &self.0
+    }
+}
+
+impl From<Bytes> for SerdeBytes {
+    fn from(value: Bytes) -> Self {
+        Self(value)
+    }
+}
+
+impl From<SerdeBytes> for Bytes {
+    fn from(value: SerdeBytes) -> Self {
+        value.0
+    }
+}
+
+impl Serialize for SerdeBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&base64_standard.encode(&self.0))
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = SerdeBytes;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a byte array or a BASE64 string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        base64_standard
+            .decode(v)
+            .map(|x| SerdeBytes(x.into()))
+            .map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(SerdeBytes(Bytes::copy_from_slice(v)))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(SerdeBytes(v.into()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut v = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(x) = seq.next_element::<u8>()? {
+            v.push(x);
+        }
+        Ok(SerdeBytes(v.into()))
+    }
+}
+
+impl<'de> Deserialize<'de> for SerdeBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(BytesVisitor)
+        } else {
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// A serde-enabled wrapper of [`ByteString`], serialized as a string.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SerdeByteString(pub ByteString);
+
+impl SerdeByteString {
+    pub fn into_inner(self) -> ByteString {
+        self.0
+    }
+}
+
+impl Deref for SerdeByteString {
+    type Target = ByteString;
+
+    fn deref(&self) -> &ByteString {
+        &self.0
+    }
+}
+
+impl From<ByteString> for SerdeByteString {
+    fn from(value: ByteString) -> Self {
+        Self(value)
+    }
+}
+
+impl From<SerdeByteString> for ByteString {
+    fn from(value: SerdeByteString) -> Self {
+        value.0
+    }
+}
+
+impl Serialize for SerdeByteString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+struct ByteStringVisitor;
+
+impl<'de> Visitor<'de> for ByteStringVisitor {
+    type Value = SerdeByteString;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(SerdeByteString(v.into()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(SerdeByteString(v.into()))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        std::str::from_utf8(v)
+            .map(|x| SerdeByteString(x.into()))
+            .map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for SerdeByteString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_string(ByteStringVisitor)
+    }
+}